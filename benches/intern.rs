@@ -29,6 +29,20 @@ fn vec_of_random_strings(string_length: usize, capacity: usize) -> Vec<String> {
     out
 }
 
+/// Unlike `vec_of_random_strings`, which reseeds for every string, this
+/// draws all strings from one generator so they are distinct.
+fn vec_of_distinct_strings(string_length: usize, capacity: usize) -> Vec<String> {
+    let mut rng = XorShiftRng::from_seed(SEED);
+    let charset = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+    (0..capacity)
+        .map(|_| {
+            (0..string_length)
+                .map(|_| charset[rng.next_u32() as usize % charset.len()] as char)
+                .collect()
+        })
+        .collect()
+}
+
 pub fn intern_and_lookup(c: &mut Criterion) {
     let vec_lengths: [u64; 3] = [1000, 10000, 100000];
     let mut group = c.benchmark_group("intern_and_lookup");
@@ -51,5 +65,126 @@ pub fn intern_and_lookup(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, intern_and_lookup);
+/// Compares arena storage with a `Box<str>` per string. Every iteration
+/// starts from an empty table, so each string is appended as a new one.
+pub fn intern_and_lookup_arena(c: &mut Criterion) {
+    let vec_lengths: [u64; 3] = [1000, 10000, 100000];
+    let mut group = c.benchmark_group("intern_and_lookup_arena");
+    for vec_length in &vec_lengths {
+        let data = vec_of_distinct_strings(100, *vec_length as usize);
+        group.bench_with_input(
+            BenchmarkId::new("boxed", vec_length),
+            &data,
+            |b, dataset| {
+                b.iter(|| {
+                    let mut intern = intern_string::Intern::new();
+                    for data in dataset {
+                        let id = intern.intern(black_box(data.as_str()));
+                        black_box(intern.lookup(id));
+                    }
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("arena", vec_length),
+            &data,
+            |b, dataset| {
+                b.iter(|| {
+                    let mut intern = intern_string::ArenaIntern::new();
+                    for data in dataset {
+                        let id = intern.intern(black_box(data.as_str()));
+                        black_box(intern.lookup(id));
+                    }
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+pub fn lookup_checked_and_unchecked(c: &mut Criterion) {
+    let data = vec_of_distinct_strings(100, 10000);
+    let mut intern = intern_string::Intern::new();
+    let ids = intern.intern_all(&data);
+    let mut group = c.benchmark_group("lookup");
+    group.bench_function("checked", |b| {
+        b.iter(|| {
+            for id in &ids {
+                black_box(intern.lookup(black_box(*id)));
+            }
+        });
+    });
+    group.bench_function("unchecked", |b| {
+        b.iter(|| {
+            for id in &ids {
+                // SAFETY: every id was returned by `intern_all` above.
+                black_box(unsafe { intern.lookup_unchecked(black_box(*id)) });
+            }
+        });
+    });
+    group.finish();
+}
+
+pub fn intern_short_strings(c: &mut Criterion) {
+    // strings of 2 to 8 bytes, cut from distinct ones. Only some of the
+    // shortest can repeat.
+    let data: Vec<String> = vec_of_distinct_strings(8, 10000)
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut s)| {
+            s.truncate(2 + i % 7);
+            s
+        })
+        .collect();
+    let mut group = c.benchmark_group("intern_short_strings");
+    group.bench_function("boxed", |b| {
+        b.iter(|| {
+            let mut intern = intern_string::Intern::with_capacity(data.len());
+            for s in &data {
+                black_box(intern.intern(black_box(s.as_str())));
+            }
+        });
+    });
+    group.bench_function("inline", |b| {
+        b.iter(|| {
+            let mut intern = intern_string::SmallIntern::with_capacity(data.len());
+            for s in &data {
+                black_box(intern.intern(black_box(s.as_str())));
+            }
+        });
+    });
+    group.finish();
+}
+
+pub fn intern_all_hits(c: &mut Criterion) {
+    let vec_lengths: [u64; 3] = [1000, 10000, 100000];
+    let mut group = c.benchmark_group("intern_all_hits");
+    for vec_length in &vec_lengths {
+        let data = vec_of_distinct_strings(16, *vec_length as usize);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(vec_length),
+            &data,
+            |b, dataset| {
+                let mut intern = intern_string::Intern::new();
+                intern.intern_all(dataset);
+                assert_eq!(intern.len(), dataset.len());
+                b.iter(|| {
+                    for data in dataset {
+                        black_box(intern.intern(black_box(data.as_str())));
+                    }
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    intern_and_lookup,
+    intern_and_lookup_arena,
+    lookup_checked_and_unchecked,
+    intern_short_strings,
+    intern_all_hits
+);
 criterion_main!(benches);
@@ -0,0 +1,79 @@
+use codspeed_criterion_compat::{
+    black_box, criterion_group, criterion_main, BenchmarkId, Criterion,
+};
+use intern_string::{ShardedIntern, SyncIntern};
+use rand_core::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+const SEED: [u8; 16] = [1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1];
+const THREADS: usize = 8;
+const PER_THREAD: usize = 2000;
+
+fn vec_of_distinct_strings(string_length: usize, capacity: usize) -> Vec<String> {
+    let mut rng = XorShiftRng::from_seed(SEED);
+    let charset = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+    (0..capacity)
+        .map(|_| {
+            (0..string_length)
+                .map(|_| charset[rng.next_u32() as usize % charset.len()] as char)
+                .collect()
+        })
+        .collect()
+}
+
+/// The strings each thread interns. Disjoint sets share no strings, while
+/// overlapping sets are the same strings, in a different order per thread.
+fn workloads(overlapping: bool) -> Vec<Vec<String>> {
+    if overlapping {
+        let strings = vec_of_distinct_strings(20, PER_THREAD);
+        (0..THREADS)
+            .map(|t| {
+                let mut strings = strings.clone();
+                strings.rotate_left(t * PER_THREAD / THREADS);
+                strings
+            })
+            .collect()
+    } else {
+        vec_of_distinct_strings(20, THREADS * PER_THREAD)
+            .chunks(PER_THREAD)
+            .map(<[String]>::to_vec)
+            .collect()
+    }
+}
+
+/// Intern every workload on a thread of its own.
+fn run<F: Fn(&str) -> u32 + Sync>(workloads: &[Vec<String>], intern: F) {
+    std::thread::scope(|s| {
+        for strings in workloads {
+            let intern = &intern;
+            s.spawn(move || {
+                for string in strings {
+                    black_box(intern(black_box(string)));
+                }
+            });
+        }
+    });
+}
+
+pub fn concurrent_intern(c: &mut Criterion) {
+    let mut group = c.benchmark_group("concurrent_intern");
+    for (name, overlapping) in [("disjoint", false), ("overlapping", true)] {
+        let data = workloads(overlapping);
+        group.bench_with_input(BenchmarkId::new("sync", name), &data, |b, data| {
+            b.iter(|| {
+                let intern = SyncIntern::new();
+                run(data, |s| intern.intern(s));
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("sharded", name), &data, |b, data| {
+            b.iter(|| {
+                let intern = ShardedIntern::new();
+                run(data, |s| intern.intern(s));
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, concurrent_intern);
+criterion_main!(benches);
@@ -0,0 +1,365 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::BuildHasher;
+use core::ops::Index;
+
+use hashbrown::hash_table::Entry;
+use hashbrown::HashTable;
+use rustc_hash::FxBuildHasher;
+
+use crate::{invalid_id, InternError, InternId, Symbol};
+
+/// The default size of an arena chunk, in bytes.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The location of an interned string inside of the arena.
+#[derive(Clone, Copy)]
+struct Span {
+    chunk: usize,
+    start: usize,
+    end: usize,
+}
+
+/// A string intern table which stores strings in a shared arena.
+///
+/// Instead of allocating every string separately like [`Intern`](crate::Intern),
+/// `ArenaIntern` appends string bytes into large chunks of 64 KiB. This
+/// drastically reduces the number of heap allocations and keeps short strings
+/// close together in memory. Strings longer than a chunk get a chunk of their
+/// own.
+///
+/// Chunks are never freed or reallocated while the table is alive, so the
+/// memory of partially filled chunks is only reclaimed when the table is
+/// cleared or dropped.
+///
+/// # Examples
+///
+/// ```
+/// use intern_string::ArenaIntern;
+///
+/// let mut intern = ArenaIntern::new();
+/// let id = intern.intern("hello");
+/// assert_eq!(intern.lookup(id), "hello");
+/// ```
+pub struct ArenaIntern<Sym = InternId, S = FxBuildHasher> {
+    data: HashTable<Sym>,
+    spans: Vec<Span>,
+    chunks: Vec<String>,
+    hasher: S,
+}
+
+impl ArenaIntern {
+    /// Create a new arena intern table.
+    /// The table uses the default `FxBuildHasher`.
+    pub fn new() -> Self {
+        Self::with_hasher(FxBuildHasher)
+    }
+
+    /// Create a new arena intern table with room for `capacity` strings.
+    /// The table uses the default `FxBuildHasher`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: HashTable::with_capacity(capacity),
+            spans: Vec::with_capacity(capacity),
+            chunks: Vec::new(),
+            hasher: FxBuildHasher,
+        }
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> ArenaIntern<Sym, S> {
+    /// Create a new arena intern table which will use the given hasher.
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            data: HashTable::new(),
+            spans: Vec::new(),
+            chunks: Vec::new(),
+            hasher,
+        }
+    }
+
+    /// Intern a string.
+    /// Returns the interned id.
+    /// If the string is already interned, returns the existing id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every id is already in use. See [`ArenaIntern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::ArenaIntern;
+    ///
+    /// let mut intern = ArenaIntern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.intern("hello"), id);
+    /// ```
+    #[inline]
+    pub fn intern<V: AsRef<str>>(&mut self, input: V) -> Sym {
+        match self.try_intern(input) {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Intern a string.
+    /// Returns the interned id, or an error if the string could not be interned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::IdSpaceExhausted`] if every id is already in use.
+    #[inline]
+    pub fn try_intern<V: AsRef<str>>(&mut self, input: V) -> Result<Sym, InternError> {
+        let input = input.as_ref();
+        let hash = self.hasher.hash_one(input);
+        let Self {
+            data,
+            spans,
+            chunks,
+            hasher,
+        } = self;
+        let entry = data.entry(
+            hash,
+            |id| resolve(chunks, spans[id.to_usize()]) == input,
+            |id| hasher.hash_one(resolve(chunks, spans[id.to_usize()])),
+        );
+        match entry {
+            Entry::Occupied(entry) => Ok(*entry.get()),
+            Entry::Vacant(entry) => {
+                if spans.len() > Sym::MAX {
+                    return Err(InternError::IdSpaceExhausted);
+                }
+                let id = Sym::from_usize(spans.len());
+                spans.push(alloc(chunks, input));
+                entry.insert(id);
+                Ok(id)
+            }
+        }
+    }
+
+    /// Find the id of `input` in `data`, given its hash.
+    #[inline]
+    fn find(&self, hash: u64, input: &str) -> Option<Sym> {
+        self.data
+            .find(hash, |id| {
+                resolve(&self.chunks, self.spans[id.to_usize()]) == input
+            })
+            .copied()
+    }
+
+    /// Get the id of an already interned string.
+    /// Returns `None` if the string has not been interned.
+    #[inline]
+    pub fn get(&self, input: &str) -> Option<Sym> {
+        self.find(self.hasher.hash_one(input), input)
+    }
+
+    /// Check whether a string has already been interned.
+    #[inline]
+    pub fn contains(&self, input: &str) -> bool {
+        self.get(input).is_some()
+    }
+
+    /// Lookup the interned string by id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    #[track_caller]
+    pub fn lookup(&self, id: Sym) -> &str {
+        match self.spans.get(id.to_usize()) {
+            Some(&span) => resolve(&self.chunks, span),
+            None => invalid_id(id.to_usize(), self.spans.len()),
+        }
+    }
+
+    /// Lookup the interned string by id.
+    /// Returns `None` if the id is not valid.
+    #[inline]
+    pub fn try_lookup(&self, id: Sym) -> Option<&str> {
+        let span = *self.spans.get(id.to_usize())?;
+        Some(resolve(&self.chunks, span))
+    }
+
+    /// Returns the number of unique strings in the intern table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// Returns `true` if no strings have been interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Iterate over all interned strings in insertion order.
+    /// The position of each string in the iteration is its id.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.spans.iter().map(|&span| resolve(&self.chunks, span))
+    }
+
+    /// Reserve room for at least `bytes` more bytes of string data.
+    ///
+    /// If the current chunk does not have enough room left, a new chunk of
+    /// at least `bytes` bytes is allocated up front, so a large bulk load
+    /// does not allocate a new chunk every 64 KiB.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::ArenaIntern;
+    ///
+    /// let mut intern = ArenaIntern::new();
+    /// intern.reserve_strings_bytes(1 << 20);
+    /// for i in 0..10_000 {
+    ///     intern.intern(i.to_string());
+    /// }
+    /// ```
+    pub fn reserve_strings_bytes(&mut self, bytes: usize) {
+        let fits = self
+            .chunks
+            .last()
+            .is_some_and(|chunk| chunk.capacity() - chunk.len() >= bytes);
+        if !fits {
+            self.chunks
+                .push(String::with_capacity(CHUNK_SIZE.max(bytes)));
+        }
+    }
+
+    /// Remove every string from the intern table.
+    /// All previously returned ids become invalid and new ids start at 0 again.
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.spans.clear();
+        self.chunks.clear();
+    }
+}
+
+/// Copy `input` into the arena, starting a new chunk if the current one
+/// does not have enough room left.
+fn alloc(chunks: &mut Vec<String>, input: &str) -> Span {
+    let fits = chunks
+        .last()
+        .is_some_and(|chunk| chunk.capacity() - chunk.len() >= input.len());
+    if !fits {
+        chunks.push(String::with_capacity(CHUNK_SIZE.max(input.len())));
+    }
+
+    let chunk = chunks.len() - 1;
+    let buf = &mut chunks[chunk];
+    let start = buf.len();
+    // never reallocates, since the chunk was checked to have enough room.
+    buf.push_str(input);
+    Span {
+        chunk,
+        start,
+        end: buf.len(),
+    }
+}
+
+/// Resolve a span to the string it refers to.
+#[inline]
+fn resolve(chunks: &[String], span: Span) -> &str {
+    &chunks[span.chunk][span.start..span.end]
+}
+
+impl<Sym, S: Default> Default for ArenaIntern<Sym, S> {
+    fn default() -> Self {
+        Self {
+            data: HashTable::new(),
+            spans: Vec::new(),
+            chunks: Vec::new(),
+            hasher: S::default(),
+        }
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> fmt::Debug for ArenaIntern<Sym, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArenaIntern")
+            .field("len", &self.len())
+            .field("chunks", &self.chunks.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> Index<Sym> for ArenaIntern<Sym, S> {
+    type Output = str;
+
+    /// Lookup the interned string by id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    #[track_caller]
+    fn index(&self, id: Sym) -> &str {
+        self.lookup(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interns_into_shared_chunks() {
+        let mut interner = ArenaIntern::new();
+        let hello = interner.intern("hello");
+        let world = interner.intern("world");
+        assert_eq!(interner.intern("hello"), hello);
+        assert_eq!(interner.lookup(hello), "hello");
+        assert_eq!(&interner[world], "world");
+        assert_eq!(interner.get("world"), Some(world));
+        assert_eq!(interner.try_lookup(2), None);
+        assert_eq!(interner.chunks.len(), 1);
+    }
+
+    #[test]
+    fn starts_new_chunks_when_full() {
+        let mut interner = ArenaIntern::new();
+        let long = "a".repeat(CHUNK_SIZE + 1);
+        let small = interner.intern("small");
+        let big = interner.intern(&long);
+        let after = interner.intern("after");
+
+        assert_eq!(interner.chunks.len(), 3);
+        assert_eq!(interner.lookup(small), "small");
+        assert_eq!(interner.lookup(big), long);
+        assert_eq!(interner.lookup(after), "after");
+        assert_eq!(
+            interner.iter().map(str::len).collect::<Vec<_>>(),
+            [5, CHUNK_SIZE + 1, 5]
+        );
+
+        interner.clear();
+        assert!(interner.is_empty());
+        assert_eq!(interner.intern("after"), 0);
+    }
+
+    #[test]
+    fn reserves_a_single_chunk() {
+        let mut interner = ArenaIntern::new();
+        interner.reserve_strings_bytes(4 * CHUNK_SIZE);
+        for i in 0..4 {
+            interner.intern(char::from(b'a' + i).to_string().repeat(CHUNK_SIZE));
+        }
+        assert_eq!(interner.chunks.len(), 1);
+
+        interner.reserve_strings_bytes(1);
+        assert_eq!(interner.chunks.len(), 2);
+    }
+
+    #[test]
+    #[should_panic = "InternId 1 out of range (only 1 strings interned)"]
+    fn lookup_names_invalid_id() {
+        let mut interner = ArenaIntern::new();
+        interner.intern("a");
+        interner.lookup(1);
+    }
+}
@@ -0,0 +1,102 @@
+use alloc::string::{String, ToString};
+use core::hash::BuildHasher;
+
+use borsh::io::{Error, ErrorKind, Read, Result, Write};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::{Intern, Symbol};
+
+/// Serializes the intern table like a `Vec<String>` of the strings in id order.
+/// Fails if the table has gaps, see [`Intern::has_gaps`].
+impl<Sym: Symbol, H: BuildHasher> BorshSerialize for Intern<'_, Sym, H> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if self.has_gaps() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "cannot serialize an intern table with gaps",
+            ));
+        }
+        let len = u32::try_from(self.len())
+            .map_err(|_| Error::new(ErrorKind::InvalidData, "intern table is too long"))?;
+        len.serialize(writer)?;
+        for s in self.iter() {
+            s.serialize(writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deserializes a `Vec<String>` of unique strings, assigning ids by position.
+impl<Sym: Symbol, H: BuildHasher + Default> BorshDeserialize for Intern<'_, Sym, H> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        let len = u32::deserialize_reader(reader)? as usize;
+        let mut intern = Intern::<Sym, H>::with_hasher(H::default());
+        // the length is untrusted, so do not reserve for all of it up front.
+        intern.reserve(len.min(1024));
+        for expected in 0..len {
+            let s = String::deserialize_reader(reader)?;
+            let id = intern
+                .try_intern(s)
+                .map_err(|err| Error::new(ErrorKind::InvalidData, err.to_string()))?;
+            if id.to_usize() != expected {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "duplicate string in intern table",
+                ));
+            }
+        }
+        Ok(intern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InternError;
+
+    #[test]
+    fn round_trip_preserves_ids() {
+        let mut intern = Intern::new();
+        for s in ["hello", "world", "foo", "hello", "bar"] {
+            intern.intern(s);
+        }
+
+        let bytes = borsh::to_vec(&intern).unwrap();
+        let strings: Vec<String> = borsh::from_slice(&bytes).unwrap();
+        assert_eq!(strings, ["hello", "world", "foo", "bar"]);
+
+        let restored: Intern = borsh::from_slice(&bytes).unwrap();
+        assert_eq!(restored, intern);
+        for (id, s) in intern.iter_with_ids() {
+            assert_eq!(restored.lookup(id), s);
+            assert_eq!(restored.get(s), Some(id));
+        }
+    }
+
+    #[test]
+    fn rejects_duplicates() {
+        let bytes = borsh::to_vec(&["a", "b", "a"].map(String::from).to_vec()).unwrap();
+        assert!(borsh::from_slice::<Intern>(&bytes).is_err());
+    }
+
+    #[test]
+    fn refuses_tables_with_gaps() {
+        let mut intern = Intern::new();
+        intern.intern_at(2, "two").unwrap();
+        intern.intern_at(3, "").unwrap();
+        assert!(borsh::to_vec(&intern).is_err());
+        // gaps written as empty strings are read back as duplicates.
+        let bytes = borsh::to_vec(&["", "", "two"].map(String::from).to_vec()).unwrap();
+        assert!(borsh::from_slice::<Intern>(&bytes).is_err());
+
+        intern.intern_at(0, "zero").unwrap();
+        intern.intern_at(1, "one").unwrap();
+        let bytes = borsh::to_vec(&intern).unwrap();
+        let mut restored: Intern = borsh::from_slice(&bytes).unwrap();
+        assert_eq!(restored, intern);
+        assert_eq!(restored.get(""), Some(3));
+        assert_eq!(restored.get("two"), Some(2));
+        assert_eq!(restored.intern_at(1, "x"), Err(InternError::IdConflict));
+        assert_eq!(restored.validate(), Ok(()));
+    }
+}
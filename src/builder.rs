@@ -0,0 +1,276 @@
+use core::fmt;
+use core::hash::BuildHasher;
+use core::mem;
+
+use rustc_hash::FxBuildHasher;
+
+use crate::{owned, Intern, InternError, InternId, Symbol};
+
+/// Builds a string piece by piece in a buffer owned by the intern table,
+/// then interns it.
+///
+/// Created by [`Intern::builder`]. The buffer is reused for every string, so
+/// building a string which is already interned does not allocate at all. A
+/// new string is copied out of the buffer when it is interned.
+///
+/// # Examples
+///
+/// ```
+/// use intern_string::Intern;
+/// use std::fmt::Write;
+///
+/// let mut intern = Intern::new();
+/// let mut builder = intern.builder();
+/// write!(builder, "item_{}", 7).unwrap();
+/// let id = builder.finish();
+/// assert_eq!(intern.lookup(id), "item_7");
+/// ```
+pub struct InternBuilder<'b, 'a, Sym = InternId, S = FxBuildHasher> {
+    intern: &'b mut Intern<'a, Sym, S>,
+}
+
+impl<'a, Sym: Symbol, S: BuildHasher> Intern<'a, Sym, S> {
+    /// Start building a string to intern. See [`InternBuilder`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let mut builder = intern.builder();
+    /// builder.push_str("hello");
+    /// builder.push('!');
+    /// let id = builder.finish();
+    /// assert_eq!(intern.get("hello!"), Some(id));
+    /// ```
+    #[inline]
+    pub fn builder(&mut self) -> InternBuilder<'_, 'a, Sym, S> {
+        self.scratch.clear();
+        InternBuilder { intern: self }
+    }
+
+    /// Intern the concatenation of string fragments.
+    /// Returns the interned id.
+    /// If the string is already interned, returns the existing id.
+    ///
+    /// The fragments are joined in a buffer owned by the table, so nothing is
+    /// allocated unless the string is new.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`Intern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern_fragments(["hel", "lo"]);
+    /// assert_eq!(intern.intern("hello"), id);
+    /// ```
+    pub fn intern_fragments<'f, I: IntoIterator<Item = &'f str>>(&mut self, fragments: I) -> Sym {
+        let mut builder = self.builder();
+        for fragment in fragments {
+            builder.push_str(fragment);
+        }
+        builder.finish()
+    }
+
+    /// Intern a formatted string.
+    /// Returns the interned id.
+    /// If the string is already interned, returns the existing id.
+    ///
+    /// The string is formatted into a buffer owned by the table, so unlike
+    /// interning the result of `format!`, nothing is allocated unless the
+    /// string is new.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned, see [`Intern::try_intern`],
+    /// or if a formatting trait implementation returns an error, like `format!`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let (name, index) = ("tmp", 3);
+    /// let id = intern.intern_fmt(format_args!("{name}_{index}"));
+    /// assert_eq!(intern.lookup(id), "tmp_3");
+    /// ```
+    pub fn intern_fmt(&mut self, args: fmt::Arguments<'_>) -> Sym {
+        let mut builder = self.builder();
+        fmt::Write::write_fmt(&mut builder, args)
+            .expect("a formatting trait implementation returned an error");
+        builder.finish()
+    }
+
+    /// Intern the ASCII lowercase form of a string.
+    /// Returns the interned id.
+    /// If the lowercase form is already interned, returns the existing id.
+    ///
+    /// Only the lowercase form is stored, so [`Intern::lookup`] returns it.
+    /// A string without ASCII uppercase letters is interned as is. Otherwise it
+    /// is lowercased in a buffer owned by the table, so nothing is allocated
+    /// unless the lowercase form is new.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`Intern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern_ascii_lower("Content-Type");
+    /// assert_eq!(intern.intern_ascii_lower("content-type"), id);
+    /// assert_eq!(intern.lookup(id), "content-type");
+    /// ```
+    pub fn intern_ascii_lower(&mut self, input: &str) -> Sym {
+        if !input.bytes().any(|b| b.is_ascii_uppercase()) {
+            return self.intern(input);
+        }
+        let mut builder = self.builder();
+        builder.push_str(input);
+        builder.intern.scratch.make_ascii_lowercase();
+        builder.finish()
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> InternBuilder<'_, '_, Sym, S> {
+    /// Append a string fragment.
+    #[inline]
+    pub fn push_str(&mut self, fragment: &str) {
+        self.intern.scratch.push_str(fragment);
+    }
+
+    /// Append a single character.
+    #[inline]
+    pub fn push(&mut self, c: char) {
+        self.intern.scratch.push(c);
+    }
+
+    /// Returns the string built so far.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.intern.scratch
+    }
+
+    /// Intern the built string.
+    /// Returns the interned id.
+    /// If the string is already interned, returns the existing id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`InternBuilder::try_finish`].
+    #[inline]
+    pub fn finish(self) -> Sym {
+        match self.try_finish() {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Intern the built string.
+    /// Returns the interned id, or an error if the string could not be interned.
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`Intern::try_intern`].
+    pub fn try_finish(self) -> Result<Sym, InternError> {
+        let intern = self.intern;
+        // the buffer is moved out while interning, since the table is borrowed
+        // mutably, and put back afterwards to keep its allocation.
+        let mut scratch = mem::take(&mut intern.scratch);
+        let hash = intern.hasher.hash_one(scratch.as_str());
+        let result = intern.try_intern_hashed(hash, scratch.as_str(), owned);
+        scratch.clear();
+        intern.scratch = scratch;
+        result
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> fmt::Write for InternBuilder<'_, '_, Sym, S> {
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+
+    #[inline]
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.push(c);
+        Ok(())
+    }
+}
+
+impl<Sym, S> fmt::Debug for InternBuilder<'_, '_, Sym, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InternBuilder")
+            .field("buf", &self.intern.scratch)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+
+    #[test]
+    fn builder_reuses_buffer() {
+        let mut interner = Intern::new();
+        let hello = interner.intern_fragments(["he", "", "llo"]);
+        assert_eq!(interner.lookup(hello), "hello");
+        let capacity = interner.scratch.capacity();
+        assert!(capacity >= 5);
+
+        let mut builder = interner.builder();
+        let l = 'l';
+        write!(builder, "he{l}{l}").unwrap();
+        builder.push('o');
+        assert_eq!(builder.as_str(), "hello");
+        assert_eq!(builder.finish(), hello);
+        assert!(interner.scratch.is_empty());
+        assert_eq!(interner.scratch.capacity(), capacity);
+
+        // an abandoned builder leaves nothing behind.
+        interner.builder().push_str("abandoned");
+        let world = interner.intern_fragments(["wor", "ld"]);
+        assert_eq!(interner.lookup(world), "world");
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    fn intern_fmt_reuses_buffer() {
+        let mut interner = Intern::new();
+        let id = interner.intern_fmt(format_args!("{}_{}", "a", 1));
+        let capacity = interner.scratch.capacity();
+        for _ in 0..3 {
+            assert_eq!(interner.intern_fmt(format_args!("{}_{}", "a", 1)), id);
+        }
+        assert_eq!(interner.scratch.capacity(), capacity);
+        assert_eq!(interner.get("a_1"), Some(id));
+        assert_eq!(interner.len(), 1);
+    }
+
+    #[test]
+    fn intern_ascii_lower_stores_lowercase() {
+        let mut interner = Intern::new();
+        let lower = interner.intern_ascii_lower("hello");
+        let id = interner.intern_ascii_lower("HeLLo");
+        assert_eq!(id, lower);
+        assert_eq!(interner.intern("hello"), id);
+
+        let unicode = interner.intern_ascii_lower("ÄBC");
+        assert_eq!(interner.lookup(unicode), "Äbc");
+        assert_eq!(interner.get("ÄBC"), None);
+        assert!(interner.scratch.is_empty());
+        assert_eq!(interner.len(), 2);
+    }
+}
@@ -0,0 +1,249 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::BuildHasher;
+use core::ops::Index;
+
+use hashbrown::hash_table::Entry;
+use hashbrown::HashTable;
+use rustc_hash::FxBuildHasher;
+
+use crate::{invalid_id, rehash, InternError, InternId, Symbol};
+
+/// A byte string intern table.
+///
+/// `ByteIntern` behaves exactly like [`Intern`](crate::Intern), but stores
+/// arbitrary bytes instead of UTF-8 strings. This makes it suitable for file
+/// paths, protocol tokens and other data that is not guaranteed to be valid
+/// UTF-8.
+pub struct ByteIntern<Sym = InternId, S = FxBuildHasher> {
+    data: HashTable<Sym>,
+    list: Vec<Box<[u8]>>,
+    hasher: S,
+}
+
+impl ByteIntern {
+    /// Create a new byte intern table.
+    /// The table uses the default `FxBuildHasher`.
+    pub fn new() -> Self {
+        Self::with_hasher(FxBuildHasher)
+    }
+
+    /// Create a new byte intern table with the given capacity.
+    /// The table uses the default `FxBuildHasher`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: HashTable::with_capacity(capacity),
+            list: Vec::with_capacity(capacity),
+            hasher: FxBuildHasher,
+        }
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> ByteIntern<Sym, S> {
+    /// Create a new byte intern table which will use the given hasher.
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            data: HashTable::new(),
+            list: Vec::new(),
+            hasher,
+        }
+    }
+
+    /// Intern a byte string.
+    /// Returns the interned id.
+    /// If the byte string is already interned, returns the existing id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every id is already in use. See [`ByteIntern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::ByteIntern;
+    ///
+    /// let mut intern = ByteIntern::new();
+    /// let id = intern.intern(&b"\xffhello"[..]);
+    /// assert_eq!(intern.lookup(id), b"\xffhello");
+    /// ```
+    #[inline]
+    pub fn intern<V: Into<Vec<u8>> + AsRef<[u8]>>(&mut self, input: V) -> Sym {
+        match self.try_intern(input) {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Intern a byte string.
+    /// Returns the interned id, or an error if the byte string could not be interned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::IdSpaceExhausted`] if every id is already in use.
+    #[inline]
+    pub fn try_intern<V: Into<Vec<u8>> + AsRef<[u8]>>(
+        &mut self,
+        input: V,
+    ) -> Result<Sym, InternError> {
+        let hash = self.hasher.hash_one(input.as_ref());
+        let Self {
+            data, list, hasher, ..
+        } = self;
+        let entry = data.entry(
+            hash,
+            |id| *list[id.to_usize()] == *input.as_ref(),
+            rehash(list, hasher),
+        );
+        match entry {
+            Entry::Occupied(entry) => Ok(*entry.get()),
+            Entry::Vacant(entry) => {
+                if list.len() > Sym::MAX {
+                    return Err(InternError::IdSpaceExhausted);
+                }
+                let id = Sym::from_usize(list.len());
+                list.push(input.into().into_boxed_slice());
+                entry.insert(id);
+                Ok(id)
+            }
+        }
+    }
+
+    /// Get the id of an already interned byte string.
+    /// Returns `None` if the byte string has not been interned.
+    #[inline]
+    pub fn get(&self, input: &[u8]) -> Option<Sym> {
+        self.find(self.hasher.hash_one(input), input)
+    }
+
+    /// Find the id of `input` in `data`, given its hash.
+    #[inline]
+    fn find(&self, hash: u64, input: &[u8]) -> Option<Sym> {
+        self.data
+            .find(hash, |id| *self.list[id.to_usize()] == *input)
+            .copied()
+    }
+
+    /// Check whether a byte string has already been interned.
+    #[inline]
+    pub fn contains(&self, input: &[u8]) -> bool {
+        self.get(input).is_some()
+    }
+
+    /// Lookup the interned byte string by id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    #[track_caller]
+    pub fn lookup(&self, id: Sym) -> &[u8] {
+        match self.list.get(id.to_usize()) {
+            Some(s) => s,
+            None => invalid_id(id.to_usize(), self.list.len()),
+        }
+    }
+
+    /// Lookup the interned byte string by id.
+    /// Returns `None` if the id is not valid.
+    #[inline]
+    pub fn try_lookup(&self, id: Sym) -> Option<&[u8]> {
+        self.list.get(id.to_usize()).map(|s| &**s)
+    }
+
+    /// Returns the number of unique byte strings in the intern table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns `true` if no byte strings have been interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Iterate over all interned byte strings in insertion order.
+    /// The position of each byte string in the iteration is its id.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        self.list.iter().map(|s| &**s)
+    }
+}
+
+impl<Sym, S: Default> Default for ByteIntern<Sym, S> {
+    fn default() -> Self {
+        Self {
+            data: HashTable::new(),
+            list: Vec::new(),
+            hasher: S::default(),
+        }
+    }
+}
+
+impl<Sym, S> PartialEq for ByteIntern<Sym, S> {
+    /// Two intern tables are equal if they hold the same byte strings under the same ids.
+    fn eq(&self, other: &Self) -> bool {
+        self.list == other.list
+    }
+}
+
+impl<Sym, S> Eq for ByteIntern<Sym, S> {}
+
+impl<Sym, S> fmt::Debug for ByteIntern<Sym, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Strings<'a>(&'a [Box<[u8]>]);
+
+        impl fmt::Debug for Strings<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_map().entries(self.0.iter().enumerate()).finish()
+            }
+        }
+
+        f.debug_struct("ByteIntern")
+            .field("len", &self.list.len())
+            .field("strings", &Strings(&self.list))
+            .finish()
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> Index<Sym> for ByteIntern<Sym, S> {
+    type Output = [u8];
+
+    /// Lookup the interned byte string by id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    #[track_caller]
+    fn index(&self, id: Sym) -> &[u8] {
+        self.lookup(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interns_invalid_utf8() {
+        let mut interner = ByteIntern::new();
+        let a = interner.intern(vec![0xff, 0xfe]);
+        let b = interner.intern(&b"hello"[..]);
+        assert_eq!(interner.intern(&[0xff, 0xfe][..]), a);
+        assert_eq!(interner.lookup(a), [0xff, 0xfe]);
+        assert_eq!(&interner[b], b"hello");
+        assert_eq!(interner.get(b"hello"), Some(b));
+        assert_eq!(interner.try_lookup(2), None);
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    #[should_panic = "InternId 1 out of range (only 1 strings interned)"]
+    fn lookup_names_invalid_id() {
+        let mut interner = ByteIntern::new();
+        interner.intern(b"a");
+        interner.lookup(1);
+    }
+}
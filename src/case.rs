@@ -0,0 +1,255 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{BuildHasher, Hasher};
+use core::ops::Index;
+
+use hashbrown::hash_table::Entry;
+use hashbrown::HashTable;
+use rustc_hash::FxBuildHasher;
+
+use crate::{invalid_id, InternError, InternId, Symbol};
+
+/// A string intern table which ignores ASCII case.
+///
+/// Strings which only differ in ASCII case, like `Content-Type` and
+/// `content-type`, are interned under the same id. The table keeps the casing
+/// of the first string it saw, and returns that from `lookup`.
+///
+/// Only ASCII letters are folded: `É` and `é` are still treated as different
+/// strings. This matches the case-insensitivity rules of protocols like HTTP,
+/// and is much cheaper than full Unicode case folding.
+///
+/// # Examples
+///
+/// ```
+/// use intern_string::CaseInsensitiveIntern;
+///
+/// let mut intern = CaseInsensitiveIntern::new();
+/// let id = intern.intern("Content-Type");
+/// assert_eq!(intern.intern("CONTENT-TYPE"), id);
+/// assert_eq!(intern.lookup(id), "Content-Type");
+/// ```
+pub struct CaseInsensitiveIntern<Sym = InternId, S = FxBuildHasher> {
+    data: HashTable<Sym>,
+    list: Vec<Box<str>>,
+    hasher: S,
+}
+
+/// Hash a string as if it was ASCII-lowercased.
+fn hash_ignore_ascii_case<S: BuildHasher>(hasher: &S, input: &str) -> u64 {
+    let mut state = hasher.build_hasher();
+    let mut buf = [0; 64];
+    for chunk in input.as_bytes().chunks(buf.len()) {
+        let buf = &mut buf[..chunk.len()];
+        buf.copy_from_slice(chunk);
+        buf.make_ascii_lowercase();
+        state.write(buf);
+    }
+    state.finish()
+}
+
+impl CaseInsensitiveIntern {
+    /// Create a new case-insensitive intern table.
+    /// The table uses the default `FxBuildHasher`.
+    pub fn new() -> Self {
+        Self::with_hasher(FxBuildHasher)
+    }
+
+    /// Create a new case-insensitive intern table with the given capacity.
+    /// The table uses the default `FxBuildHasher`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: HashTable::with_capacity(capacity),
+            list: Vec::with_capacity(capacity),
+            hasher: FxBuildHasher,
+        }
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> CaseInsensitiveIntern<Sym, S> {
+    /// Create a new case-insensitive intern table which will use the given hasher.
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            data: HashTable::new(),
+            list: Vec::new(),
+            hasher,
+        }
+    }
+
+    /// Intern a string, ignoring ASCII case.
+    /// Returns the interned id.
+    /// If the string is already interned in any casing, returns the existing id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every id is already in use. See [`CaseInsensitiveIntern::try_intern`].
+    #[inline]
+    pub fn intern<V: Into<String> + AsRef<str>>(&mut self, input: V) -> Sym {
+        match self.try_intern(input) {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Intern a string, ignoring ASCII case.
+    /// Returns the interned id, or an error if the string could not be interned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::IdSpaceExhausted`] if every id is already in use.
+    pub fn try_intern<V: Into<String> + AsRef<str>>(
+        &mut self,
+        input: V,
+    ) -> Result<Sym, InternError> {
+        let hash = hash_ignore_ascii_case(&self.hasher, input.as_ref());
+        let Self { data, list, hasher } = self;
+        let entry = data.entry(
+            hash,
+            |id| list[id.to_usize()].eq_ignore_ascii_case(input.as_ref()),
+            |id| hash_ignore_ascii_case(hasher, &list[id.to_usize()]),
+        );
+        match entry {
+            Entry::Occupied(entry) => Ok(*entry.get()),
+            Entry::Vacant(entry) => {
+                if list.len() > Sym::MAX {
+                    return Err(InternError::IdSpaceExhausted);
+                }
+                let id = Sym::from_usize(list.len());
+                list.push(input.into().into_boxed_str());
+                entry.insert(id);
+                Ok(id)
+            }
+        }
+    }
+
+    /// Get the id of an already interned string, ignoring ASCII case.
+    /// Returns `None` if the string has not been interned in any casing.
+    #[inline]
+    pub fn get(&self, input: &str) -> Option<Sym> {
+        let hash = hash_ignore_ascii_case(&self.hasher, input);
+        self.data
+            .find(hash, |id| {
+                self.list[id.to_usize()].eq_ignore_ascii_case(input)
+            })
+            .copied()
+    }
+
+    /// Check whether a string has already been interned, ignoring ASCII case.
+    #[inline]
+    pub fn contains(&self, input: &str) -> bool {
+        self.get(input).is_some()
+    }
+
+    /// Lookup the interned string by id.
+    /// Returns the string in the casing it was first interned with.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    #[track_caller]
+    pub fn lookup(&self, id: Sym) -> &str {
+        match self.list.get(id.to_usize()) {
+            Some(s) => s,
+            None => invalid_id(id.to_usize(), self.list.len()),
+        }
+    }
+
+    /// Lookup the interned string by id.
+    /// Returns `None` if the id is not valid.
+    #[inline]
+    pub fn try_lookup(&self, id: Sym) -> Option<&str> {
+        self.list.get(id.to_usize()).map(|s| &**s)
+    }
+
+    /// Returns the number of unique strings in the intern table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns `true` if no strings have been interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Iterate over all interned strings in insertion order.
+    /// The position of each string in the iteration is its id.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.list.iter().map(|s| &**s)
+    }
+}
+
+impl<Sym, S: Default> Default for CaseInsensitiveIntern<Sym, S> {
+    fn default() -> Self {
+        Self {
+            data: HashTable::new(),
+            list: Vec::new(),
+            hasher: S::default(),
+        }
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> fmt::Debug for CaseInsensitiveIntern<Sym, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CaseInsensitiveIntern")
+            .field("len", &self.len())
+            .field("strings", &self.list)
+            .finish()
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> Index<Sym> for CaseInsensitiveIntern<Sym, S> {
+    type Output = str;
+
+    /// Lookup the interned string by id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    #[track_caller]
+    fn index(&self, id: Sym) -> &str {
+        self.lookup(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folds_ascii_case_only() {
+        let mut interner = CaseInsensitiveIntern::new();
+        let header = interner.intern("Content-Type");
+        assert_eq!(interner.intern("content-type"), header);
+        assert_eq!(interner.intern("CONTENT-TYPE"), header);
+        assert_eq!(interner.get("cOnTeNt-TyPe"), Some(header));
+        assert_eq!(interner.lookup(header), "Content-Type");
+
+        let upper = interner.intern("É");
+        assert_ne!(interner.intern("é"), upper);
+        assert_eq!(interner.len(), 3);
+    }
+
+    #[test]
+    fn hashes_long_strings_consistently() {
+        let mut interner = CaseInsensitiveIntern::new();
+        let long = "Ab".repeat(100);
+        let id = interner.intern(long.as_str());
+        assert_eq!(interner.get(&long.to_ascii_uppercase()), Some(id));
+        assert_eq!(interner.get(&long[1..]), None);
+    }
+
+    #[test]
+    #[should_panic = "InternId 1 out of range (only 1 strings interned)"]
+    fn lookup_names_invalid_id() {
+        let mut interner = CaseInsensitiveIntern::new();
+        interner.intern("a");
+        interner.lookup(1);
+    }
+}
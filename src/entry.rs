@@ -0,0 +1,313 @@
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::BuildHasher;
+
+use hashbrown::hash_table;
+
+use crate::{check_len, owned, rehash, Intern, InternError, InternId, Symbol};
+
+/// A view into a single string of an intern table, which may or may not
+/// have been interned yet.
+///
+/// Created by [`Intern::entry`]. The string is hashed and searched once, so
+/// deciding what to do based on whether it is present does not need a
+/// separate `get` and `intern`.
+///
+/// # Examples
+///
+/// ```
+/// use intern_string::{Entry, Intern};
+///
+/// let mut intern = Intern::new();
+/// let mut new = 0;
+/// for word in ["a", "b", "a"] {
+///     match intern.entry(word) {
+///         Entry::Occupied(_) => {}
+///         Entry::Vacant(entry) => {
+///             entry.intern();
+///             new += 1;
+///         }
+///     }
+/// }
+/// assert_eq!(new, 2);
+/// ```
+pub enum Entry<'b, 'a, V, Sym = InternId> {
+    /// The string has already been interned.
+    Occupied(OccupiedEntry<'b, 'a, Sym>),
+    /// The string has not been interned yet.
+    Vacant(VacantEntry<'b, 'a, V, Sym>),
+}
+
+/// An entry for a string which has already been interned.
+/// Part of the [`Entry`] enum.
+pub struct OccupiedEntry<'b, 'a, Sym = InternId> {
+    id: Sym,
+    list: &'b [Cow<'a, str>],
+}
+
+/// An entry for a string which has not been interned yet.
+/// Part of the [`Entry`] enum.
+pub struct VacantEntry<'b, 'a, V, Sym = InternId> {
+    entry: hash_table::VacantEntry<'b, Sym>,
+    list: &'b mut Vec<Cow<'a, str>>,
+    input: V,
+    max_len: Option<usize>,
+}
+
+impl<'a, Sym: Symbol, S: BuildHasher> Intern<'a, Sym, S> {
+    /// Get the entry of a string, to intern it or inspect its existing id.
+    /// See [`Entry`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.entry("hello").or_intern();
+    /// assert_eq!(intern.entry("hello").existing(), Some(id));
+    /// assert_eq!(intern.entry("world").existing(), None);
+    /// ```
+    pub fn entry<V: Into<String> + AsRef<str>>(&mut self, input: V) -> Entry<'_, 'a, V, Sym> {
+        let hash = self.hasher.hash_one(input.as_ref());
+        let Self {
+            data,
+            list,
+            hasher,
+            max_len,
+            ..
+        } = self;
+        let entry = data.entry(
+            hash,
+            |id| *list[id.to_usize()] == *input.as_ref(),
+            rehash(list, hasher),
+        );
+        match entry {
+            hash_table::Entry::Occupied(entry) => Entry::Occupied(OccupiedEntry {
+                id: *entry.get(),
+                list,
+            }),
+            hash_table::Entry::Vacant(entry) => Entry::Vacant(VacantEntry {
+                entry,
+                list,
+                input,
+                max_len: *max_len,
+            }),
+        }
+    }
+}
+
+impl<'b, 'a, V: Into<String> + AsRef<str>, Sym: Symbol> Entry<'b, 'a, V, Sym> {
+    /// Returns the id of the string, interning it first if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string is new and could not be interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.entry("hello").or_intern();
+    /// assert_eq!(intern.entry("hello").or_intern(), id);
+    /// ```
+    #[inline]
+    pub fn or_intern(self) -> Sym {
+        match self {
+            Entry::Occupied(entry) => entry.id(),
+            Entry::Vacant(entry) => entry.intern(),
+        }
+    }
+
+    /// Returns the id of the string if it has already been interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// assert_eq!(intern.entry("hello").existing(), None);
+    /// ```
+    #[inline]
+    pub fn existing(&self) -> Option<Sym> {
+        match self {
+            Entry::Occupied(entry) => Some(entry.id()),
+            Entry::Vacant(_) => None,
+        }
+    }
+
+    /// Call `f` with the existing id if the string has already been interned.
+    /// Returns the entry, so it can be chained with `or_intern`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let mut hits = 0;
+    /// for word in ["a", "b", "a"] {
+    ///     intern.entry(word).and_modify(|_| hits += 1).or_intern();
+    /// }
+    /// assert_eq!(hits, 1);
+    /// ```
+    #[inline]
+    pub fn and_modify<F: FnOnce(Sym)>(self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &self {
+            f(entry.id());
+        }
+        self
+    }
+
+    /// Returns the string this entry is for.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// assert_eq!(intern.entry("hello").key(), "hello");
+    /// ```
+    #[inline]
+    pub fn key(&self) -> &str {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+}
+
+impl<Sym: Symbol> OccupiedEntry<'_, '_, Sym> {
+    /// Returns the id of the interned string.
+    #[inline]
+    pub fn id(&self) -> Sym {
+        self.id
+    }
+
+    /// Returns the interned string.
+    #[inline]
+    pub fn key(&self) -> &str {
+        &self.list[self.id.to_usize()]
+    }
+}
+
+impl<V: Into<String> + AsRef<str>, Sym: Symbol> VacantEntry<'_, '_, V, Sym> {
+    /// Returns the string which would be interned.
+    #[inline]
+    pub fn key(&self) -> &str {
+        self.input.as_ref()
+    }
+
+    /// Intern the string.
+    /// Returns the new id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`VacantEntry::try_intern`].
+    #[inline]
+    pub fn intern(self) -> Sym {
+        match self.try_intern() {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Intern the string.
+    /// Returns the new id, or an error if the string could not be interned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::IdSpaceExhausted`] if every id is already in use,
+    /// or [`InternError::TooLong`] if the string is longer than the maximum
+    /// length of the table.
+    pub fn try_intern(self) -> Result<Sym, InternError> {
+        let Self {
+            entry,
+            list,
+            input,
+            max_len,
+        } = self;
+        check_len(list.len(), input.as_ref(), max_len, Sym::MAX)?;
+        let id = Sym::from_usize(list.len());
+        list.push(owned(input));
+        entry.insert(id);
+        Ok(id)
+    }
+}
+
+impl<V: AsRef<str>, Sym: fmt::Debug> fmt::Debug for Entry<'_, '_, V, Sym> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Entry::Occupied(entry) => f.debug_tuple("Entry").field(entry).finish(),
+            Entry::Vacant(entry) => f.debug_tuple("Entry").field(entry).finish(),
+        }
+    }
+}
+
+impl<Sym: fmt::Debug> fmt::Debug for OccupiedEntry<'_, '_, Sym> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OccupiedEntry")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<V: AsRef<str>, Sym> fmt::Debug for VacantEntry<'_, '_, V, Sym> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VacantEntry")
+            .field("key", &self.input.as_ref())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_interns_once() {
+        let mut interner = Intern::new();
+        let hello = interner.intern("hello");
+
+        match interner.entry(String::from("hello")) {
+            Entry::Occupied(entry) => {
+                assert_eq!(entry.id(), hello);
+                assert_eq!(entry.key(), "hello");
+            }
+            Entry::Vacant(_) => panic!("hello is interned"),
+        }
+
+        let world = match interner.entry("world") {
+            Entry::Occupied(_) => panic!("world is not interned"),
+            Entry::Vacant(entry) => {
+                assert_eq!(entry.key(), "world");
+                entry.intern()
+            }
+        };
+        assert_eq!(world, 1);
+        assert_eq!(interner.get("world"), Some(world));
+        assert_eq!(interner.entry("world").existing(), Some(world));
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "too slow under miri")]
+    fn vacant_entry_reports_exhaustion() {
+        let mut interner = Intern::<u16>::default();
+        for i in 0..=u16::MAX as usize {
+            interner.intern(i.to_string());
+        }
+        match interner.entry("full") {
+            Entry::Vacant(entry) => {
+                assert_eq!(entry.try_intern(), Err(InternError::IdSpaceExhausted))
+            }
+            Entry::Occupied(_) => panic!("full is not interned"),
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use alloc::sync::Arc;
+use core::fmt;
+use core::hash::BuildHasher;
+use core::ops::Index;
+
+use rustc_hash::FxBuildHasher;
+
+use crate::{Intern, InternId, Symbol};
+
+/// A read-only string intern table.
+///
+/// Created by [`Intern::freeze`] once all strings have been interned. Since
+/// nothing can be inserted anymore, a `FrozenIntern` can be shared between
+/// threads and queried concurrently without any locking. Use
+/// [`FrozenIntern::into_mut`] to resume interning.
+///
+/// # Examples
+///
+/// ```
+/// use intern_string::Intern;
+///
+/// let mut intern = Intern::new();
+/// let id = intern.intern("hello");
+/// let frozen = intern.freeze();
+///
+/// std::thread::scope(|s| {
+///     s.spawn(|| assert_eq!(frozen.lookup(id), "hello"));
+///     s.spawn(|| assert_eq!(frozen.get("hello"), Some(id)));
+/// });
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct FrozenIntern<'a, Sym = InternId, S = FxBuildHasher> {
+    inner: Intern<'a, Sym, S>,
+}
+
+impl<'a, Sym: Symbol, S: BuildHasher> Intern<'a, Sym, S> {
+    /// Freeze the intern table, making it read-only.
+    /// See [`FrozenIntern`].
+    pub fn freeze(self) -> FrozenIntern<'a, Sym, S> {
+        FrozenIntern { inner: self }
+    }
+
+    /// Freeze the intern table and move it into an `Arc`, so that it can be
+    /// shared between threads and tasks by cloning the `Arc`.
+    /// See [`FrozenIntern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern("hello");
+    /// let shared = intern.into_arc();
+    ///
+    /// let handle = std::thread::spawn({
+    ///     let shared = Arc::clone(&shared);
+    ///     move || shared.get("hello")
+    /// });
+    /// assert_eq!(handle.join().unwrap(), Some(id));
+    /// assert_eq!(shared.lookup(id), "hello");
+    /// ```
+    pub fn into_arc(self) -> Arc<FrozenIntern<'a, Sym, S>> {
+        Arc::new(self.freeze())
+    }
+}
+
+impl<'a, Sym: Symbol, S: BuildHasher> FrozenIntern<'a, Sym, S> {
+    /// Get the id of an interned string.
+    /// Returns `None` if the string has not been interned.
+    #[inline]
+    pub fn get(&self, input: &str) -> Option<Sym> {
+        self.inner.get(input)
+    }
+
+    /// Check whether a string has been interned.
+    #[inline]
+    pub fn contains(&self, input: &str) -> bool {
+        self.inner.contains(input)
+    }
+
+    /// Lookup the interned string by id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    #[track_caller]
+    pub fn lookup(&self, id: Sym) -> &str {
+        self.inner.lookup(id)
+    }
+
+    /// Lookup the interned string by id.
+    /// Returns `None` if the id is not valid.
+    #[inline]
+    pub fn try_lookup(&self, id: Sym) -> Option<&str> {
+        self.inner.try_lookup(id)
+    }
+
+    /// Returns the number of unique strings in the intern table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if no strings have been interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Iterate over all interned strings in insertion order.
+    /// The position of each string in the iteration is its id.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.inner.iter()
+    }
+
+    /// Iterate over all interned strings together with their ids.
+    /// Ids are yielded in ascending order.
+    #[inline]
+    pub fn iter_with_ids(&self) -> impl Iterator<Item = (Sym, &str)> {
+        self.inner.iter_with_ids()
+    }
+
+    /// Turn the table back into a mutable intern table.
+    /// Every existing id remains valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let hello = intern.intern("hello");
+    ///
+    /// let mut intern = intern.freeze().into_mut();
+    /// assert_eq!(intern.intern("hello"), hello);
+    /// assert_eq!(intern.intern("world"), 1);
+    /// ```
+    pub fn into_mut(self) -> Intern<'a, Sym, S> {
+        self.inner
+    }
+}
+
+impl<'a, Sym: Symbol, S: BuildHasher> From<Intern<'a, Sym, S>> for FrozenIntern<'a, Sym, S> {
+    fn from(intern: Intern<'a, Sym, S>) -> Self {
+        intern.freeze()
+    }
+}
+
+impl<Sym, S: Default> Default for FrozenIntern<'_, Sym, S> {
+    fn default() -> Self {
+        Self {
+            inner: Intern::default(),
+        }
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> fmt::Debug for FrozenIntern<'_, Sym, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FrozenIntern").field(&self.inner).finish()
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> Index<Sym> for FrozenIntern<'_, Sym, S> {
+    type Output = str;
+
+    /// Lookup the interned string by id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    #[track_caller]
+    fn index(&self, id: Sym) -> &str {
+        self.lookup(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_sync<T: Send + Sync>(_: &T) {}
+
+    #[test]
+    fn freeze_and_thaw() {
+        let mut interner = Intern::new();
+        let hello = interner.intern("hello");
+        let world = interner.intern("world");
+
+        let frozen = interner.freeze();
+        assert_sync(&frozen);
+        assert_eq!(frozen.lookup(hello), "hello");
+        assert_eq!(&frozen[world], "world");
+        assert_eq!(frozen.get("world"), Some(world));
+        assert_eq!(frozen.try_lookup(2), None);
+        assert_eq!(frozen.iter().collect::<Vec<_>>(), ["hello", "world"]);
+
+        let mut interner = frozen.into_mut();
+        assert_eq!(interner.intern("foo"), 2);
+        assert_eq!(interner.get("hello"), Some(hello));
+    }
+
+    #[test]
+    fn share_through_arc() {
+        let mut interner = Intern::new();
+        let hello = interner.intern("hello");
+        let shared = interner.into_arc();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                std::thread::spawn(move || {
+                    assert_eq!(shared.lookup(hello), "hello");
+                    shared.get("hello")
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), Some(hello));
+        }
+        assert_eq!(Arc::strong_count(&shared), 1);
+        assert_eq!(Arc::into_inner(shared).unwrap().into_mut().len(), 1);
+    }
+}
@@ -0,0 +1,100 @@
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::vec::{self, Vec};
+use core::iter::{Enumerate, FusedIterator};
+use core::marker::PhantomData;
+use core::slice;
+
+use crate::{into_boxed, Symbol};
+
+/// An iterator over the ids and strings of an intern table.
+///
+/// Created by [`Intern::iter_with_ids`](crate::Intern::iter_with_ids), or by
+/// iterating over a `&Intern`.
+pub struct Iter<'a, Sym> {
+    inner: Enumerate<slice::Iter<'a, Cow<'a, str>>>,
+    marker: PhantomData<Sym>,
+}
+
+impl<'a, Sym> Iter<'a, Sym> {
+    pub(crate) fn new(list: &'a [Cow<'a, str>]) -> Self {
+        Self {
+            inner: list.iter().enumerate(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, Sym: Symbol> Iterator for Iter<'a, Sym> {
+    type Item = (Sym, &'a str);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(id, s)| (Sym::from_usize(id), &**s))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<Sym: Symbol> DoubleEndedIterator for Iter<'_, Sym> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|(id, s)| (Sym::from_usize(id), &**s))
+    }
+}
+
+impl<Sym: Symbol> ExactSizeIterator for Iter<'_, Sym> {}
+
+impl<Sym: Symbol> FusedIterator for Iter<'_, Sym> {}
+
+/// An owning iterator over the ids and strings of an intern table.
+///
+/// Created by calling `into_iter` on an [`Intern`](crate::Intern). Strings
+/// stored by reference are copied into a new allocation.
+pub struct IntoIter<'a, Sym> {
+    inner: Enumerate<vec::IntoIter<Cow<'a, str>>>,
+    marker: PhantomData<Sym>,
+}
+
+impl<'a, Sym> IntoIter<'a, Sym> {
+    pub(crate) fn new(list: Vec<Cow<'a, str>>) -> Self {
+        Self {
+            inner: list.into_iter().enumerate(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<Sym: Symbol> Iterator for IntoIter<'_, Sym> {
+    type Item = (Sym, Box<str>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|(id, s)| (Sym::from_usize(id), into_boxed(s)))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<Sym: Symbol> DoubleEndedIterator for IntoIter<'_, Sym> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|(id, s)| (Sym::from_usize(id), into_boxed(s)))
+    }
+}
+
+impl<Sym: Symbol> ExactSizeIterator for IntoIter<'_, Sym> {}
+
+impl<Sym: Symbol> FusedIterator for IntoIter<'_, Sym> {}
@@ -1,80 +1,545 @@
-use fxhash::{FxBuildHasher, FxHashMap};
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
-#[derive(Default)]
-pub struct Intern<'a> {
-    data: FxHashMap<&'a str, InternId>,
-    list: Vec<Box<str>>,
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{BuildHasher, Hash, Hasher};
+use core::ops::{Deref, Index};
+use hashbrown::HashTable;
+use rustc_hash::FxBuildHasher;
+
+mod arena;
+#[cfg(feature = "borsh")]
+mod borsh_impl;
+mod builder;
+mod bytes;
+mod case;
+mod entry;
+mod frozen;
+mod iter;
+mod local;
+#[cfg(feature = "unicode-normalization")]
+mod normalized;
+#[cfg(feature = "std")]
+mod path;
+#[cfg(feature = "rkyv")]
+mod rkyv_impl;
+mod rotating;
+#[cfg(feature = "serde")]
+mod serde_impl;
+#[cfg(feature = "dashmap")]
+mod sharded;
+mod small;
+mod symbol;
+#[cfg(feature = "std")]
+mod sync;
+
+pub use arena::ArenaIntern;
+pub use builder::InternBuilder;
+pub use bytes::ByteIntern;
+pub use case::CaseInsensitiveIntern;
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+pub use frozen::FrozenIntern;
+pub use iter::{IntoIter, Iter};
+pub use local::LocalIntern;
+#[cfg(feature = "unicode-normalization")]
+pub use normalized::NormalizedIntern;
+#[cfg(feature = "std")]
+pub use path::PathIntern;
+#[cfg(feature = "rkyv")]
+pub use rkyv_impl::ArchivedIntern;
+pub use rotating::RotatingIntern;
+#[cfg(feature = "dashmap")]
+pub use sharded::ShardedIntern;
+pub use small::SmallIntern;
+pub use symbol::{NonZeroSymbolId, SymbolId};
+#[cfg(feature = "std")]
+pub use sync::SyncIntern;
+
+/// A string intern table.
+///
+/// The id type `Sym` defaults to [`InternId`]; see [`Symbol`] for the other
+/// supported id widths.
+///
+/// The hasher `S` defaults to `rustc_hash::FxBuildHasher`, which is fast but not
+/// resistant to HashDoS. Use [`Intern::with_hasher`] to pick another one,
+/// e.g. `std::collections::hash_map::RandomState` for untrusted input.
+///
+/// Strings are usually copied into the table, but strings which live for
+/// `'a`, like those passed to [`Intern::intern_static`] or
+/// [`Intern::intern_borrowed`], are stored by reference instead.
+///
+/// The table holds no raw pointers, so it is `Send` and `Sync` whenever `Sym`
+/// and `S` are, as they are by default. A table can be built on one thread
+/// and then moved to, or shared with, others.
+pub struct Intern<'a, Sym = InternId, S = FxBuildHasher> {
+    /// Ids of the interned strings, hashed by the string they refer to.
+    data: HashTable<Sym>,
+    list: Vec<Cow<'a, str>>,
+    hasher: S,
+    /// The longest string which may be interned, if limited.
+    max_len: Option<usize>,
+    /// Reused by [`InternBuilder`] to build strings without allocating.
+    scratch: String,
+    #[cfg(feature = "stats")]
+    stats: Counters,
+}
+
+/// Returns a closure which rehashes an id by the string it refers to,
+/// for when `data` needs to grow or shrink.
+#[inline]
+pub(crate) fn rehash<'a, T, U, Sym, S>(list: &'a [U], hasher: &'a S) -> impl Fn(&Sym) -> u64 + 'a
+where
+    T: Hash + ?Sized,
+    U: Deref<Target = T>,
+    Sym: Symbol,
+    S: BuildHasher,
+{
+    move |id| hasher.hash_one(&*list[id.to_usize()])
+}
+
+/// Check that a new string of the given length may be interned into a table
+/// which already holds `len` strings.
+#[inline]
+fn check_len(
+    len: usize,
+    input: &str,
+    max_len: Option<usize>,
+    max_id: usize,
+) -> Result<(), InternError> {
+    if len > max_id {
+        return Err(InternError::IdSpaceExhausted);
+    }
+    if max_len.is_some_and(|max| input.len() > max) {
+        return Err(InternError::TooLong);
+    }
+    Ok(())
+}
+
+/// Copy a string into the table, dropping any excess capacity.
+#[inline]
+fn owned<'a, V: Into<String>>(input: V) -> Cow<'a, str> {
+    Cow::Owned(input.into().into_boxed_str().into())
+}
+
+/// Convert a stored string into a `Box<str>`, copying it only if it is borrowed.
+#[inline]
+pub(crate) fn into_boxed(s: Cow<'_, str>) -> Box<str> {
+    s.into_owned().into_boxed_str()
+}
+
+/// Read a little endian `u64` length, as written by [`Intern::write_to`].
+#[cfg(feature = "std")]
+fn read_len<R: std::io::Read>(reader: &mut R) -> std::io::Result<usize> {
+    let mut buf = [0; 8];
+    reader.read_exact(&mut buf)?;
+    usize::try_from(u64::from_le_bytes(buf))
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+}
+
+/// Panic for an id which is not valid in a table of `len` strings.
+#[cold]
+#[track_caller]
+fn invalid_id(id: usize, len: usize) -> ! {
+    panic!("InternId {id} out of range (only {len} strings interned)")
 }
 
 pub type InternId = u32;
 
+/// A type that can be used as the id of an interned string.
+///
+/// Implemented for `u16`, `u32`, `u64` and `usize`. Narrow ids shrink the
+/// structures that store them, at the cost of fewer unique strings.
+/// [`SymbolId`] is a newtype around [`InternId`] for extra type safety, and
+/// [`NonZeroSymbolId`] additionally makes `Option` ids free.
+///
+/// ```
+/// use intern_string::Intern;
+///
+/// let mut intern = Intern::<u16>::default();
+/// let id: u16 = intern.intern("hello");
+/// assert_eq!(intern.lookup(id), "hello");
+/// ```
+pub trait Symbol: Copy + Eq + Hash + fmt::Debug {
+    /// The largest index that can be represented by this id type.
+    const MAX: usize;
+
+    /// Convert an index into an id.
+    /// The index must not be greater than `Self::MAX`.
+    fn from_usize(index: usize) -> Self;
+
+    /// Convert an id back into an index.
+    fn to_usize(self) -> usize;
+}
+
+macro_rules! impl_symbol {
+    ($($ty:ty),*) => {
+        $(
+            impl Symbol for $ty {
+                const MAX: usize = if (<$ty>::MAX as u128) < (usize::MAX as u128) {
+                    <$ty>::MAX as usize
+                } else {
+                    usize::MAX
+                };
+
+                #[inline]
+                fn from_usize(index: usize) -> Self {
+                    debug_assert!(index <= <Self as Symbol>::MAX);
+                    index as $ty
+                }
+
+                #[inline]
+                fn to_usize(self) -> usize {
+                    self as usize
+                }
+            }
+        )*
+    };
+}
+
+impl_symbol!(u16, u32, u64, usize);
+
+/// Errors that can occur while interning a string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InternError {
+    /// Every id is already in use, so no new string can be interned.
+    IdSpaceExhausted,
+    /// The string is longer than the maximum length set with
+    /// [`Intern::set_max_len`].
+    TooLong,
+    /// Memory for the intern table could not be allocated.
+    /// Returned by [`Intern::try_reserve`].
+    AllocFailed,
+    /// The id is already taken by a different string, or the string already
+    /// has a different id. Returned by [`Intern::intern_at`].
+    IdConflict,
+}
+
+impl fmt::Display for InternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InternError::IdSpaceExhausted => f.write_str("intern table has run out of ids"),
+            InternError::TooLong => f.write_str("string is too long to be interned"),
+            InternError::AllocFailed => {
+                f.write_str("failed to allocate memory for the intern table")
+            }
+            InternError::IdConflict => f.write_str("id is already in use by another string"),
+        }
+    }
+}
+
+impl core::error::Error for InternError {}
+
+/// An inconsistency between the ids and the strings of an intern table.
+/// Returned by [`Intern::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConsistencyError {
+    /// The hash table holds an id which has no string.
+    DanglingId(usize),
+    /// The string with this id cannot be found through the hash table.
+    MissingEntry(usize),
+    /// The string with this id is also stored under an earlier id.
+    DuplicateString(usize),
+    /// The hash table holds more ids than there are interned strings.
+    ExtraEntries,
+}
+
+impl fmt::Display for ConsistencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConsistencyError::DanglingId(id) => write!(f, "id {id} has no string"),
+            ConsistencyError::MissingEntry(id) => {
+                write!(f, "string with id {id} is missing from the hash table")
+            }
+            ConsistencyError::DuplicateString(id) => {
+                write!(f, "string with id {id} is interned more than once")
+            }
+            ConsistencyError::ExtraEntries => f.write_str("hash table holds extra ids"),
+        }
+    }
+}
+
+impl core::error::Error for ConsistencyError {}
+
+/// A breakdown of the heap memory used by an intern table.
+/// Returned by [`Intern::memory_usage`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Bytes used by the interned strings themselves.
+    pub strings_bytes: usize,
+    /// Bytes used by the list which owns the strings.
+    pub list_overhead: usize,
+    /// Bytes used by the hash table from strings to ids.
+    pub map_overhead: usize,
+}
+
+impl MemoryUsage {
+    /// Returns the total number of bytes used.
+    pub fn total(&self) -> usize {
+        self.strings_bytes + self.list_overhead + self.map_overhead
+    }
+}
+
+/// A point in the history of an intern table to roll back to.
+/// Returned by [`Intern::checkpoint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    len: usize,
+}
+
+/// Hit and miss counts of an intern table.
+/// Returned by [`Intern::stats`].
+#[cfg(feature = "stats")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InternStats {
+    /// Calls to `intern` for a string which was already interned.
+    pub hits: u64,
+    /// Calls to `intern` which interned a new string.
+    pub misses: u64,
+    /// Bytes which did not have to be stored again, because the string was
+    /// already interned. This is the total length of every hit.
+    pub bytes_saved: u64,
+    /// Number of unique strings in the table.
+    pub unique: usize,
+}
+
+#[cfg(feature = "stats")]
+impl InternStats {
+    /// Returns the total number of successful `intern` calls.
+    pub fn calls(&self) -> u64 {
+        self.hits + self.misses
+    }
+
+    /// Returns the fraction of `intern` calls which found an existing string,
+    /// or `0.0` if nothing has been interned yet.
+    pub fn hit_rate(&self) -> f64 {
+        match self.calls() {
+            0 => 0.0,
+            calls => self.hits as f64 / calls as f64,
+        }
+    }
+}
+
+/// The counters behind [`InternStats`].
+#[cfg(feature = "stats")]
+#[derive(Clone, Copy, Default)]
+struct Counters {
+    hits: u64,
+    misses: u64,
+    bytes_saved: u64,
+}
+
 impl Intern<'_> {
     /// Create a new intern table.
+    /// The table uses the default `FxBuildHasher`.
     pub fn new() -> Self {
-        Self {
-            data: FxHashMap::default(),
-            list: Vec::new(),
-        }
+        Self::with_hasher(FxBuildHasher)
     }
 
     /// Create a new intern table with the given capacity.
+    /// The table uses the default `FxBuildHasher`.
     pub fn with_capacity(capacity: usize) -> Self {
-        Self {
-            data: FxHashMap::with_capacity_and_hasher(capacity, FxBuildHasher::default()),
-            list: Vec::with_capacity(capacity),
-        }
+        Self::with_capacity_and_hasher(capacity, FxBuildHasher)
     }
 
-    /// Intern a string.
-    /// Returns the interned id.
-    /// If the string is already interned, returns the existing id.
-    /// The string is stored in the intern table for the lifetime of the program.
-    /// The id is a 32-bit integer, so there can be at most 2^32 unique strings interned.
-    /// If the limit is reached, this function will panic.
-    /// The id is guaranteed to be unique for the lifetime of the program.
+    /// Create a new intern table from a collection of strings, assigning ids
+    /// in sorted order instead of insertion order.
     ///
-    /// ## Examples
+    /// The same set of strings always produces the same ids, no matter in
+    /// which order they are given, which keeps the ids reproducible across
+    /// runs. Repeated strings collapse into a single id. Strings interned
+    /// afterwards get ids in insertion order as usual.
+    ///
+    /// # Examples
     ///
     /// ```
     /// use intern_string::Intern;
     ///
-    /// let mut intern = Intern::new();
+    /// let intern = Intern::from_sorted(["b", "c", "a"]);
+    /// assert_eq!(intern.get("a"), Some(0));
+    /// assert_eq!(intern.get("c"), Some(2));
+    /// ```
+    pub fn from_sorted<I, V>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<String> + AsRef<str>,
+    {
+        let mut strings: Vec<V> = iter.into_iter().collect();
+        strings.sort_unstable_by(|a, b| a.as_ref().cmp(b.as_ref()));
+        strings.into_iter().collect()
+    }
+}
+
+impl<'a, Sym: Symbol, S: BuildHasher> Intern<'a, Sym, S> {
+    /// Create a new intern table which will use the given hasher.
+    /// The id type is not defaulted here, so it may need to be spelled out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::{Intern, InternId};
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let mut intern = Intern::<InternId, _>::with_hasher(RandomState::new());
     /// let id = intern.intern("hello");
     /// assert_eq!(intern.lookup(id), "hello");
     /// ```
-    #[inline]
-    pub fn intern<V: Into<String> + AsRef<str>>(&mut self, input: V) -> InternId {
-        if let Some(&id) = self.data.get(input.as_ref()) {
-            return id;
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(0, hasher)
+    }
+
+    /// Create a new intern table with the given capacity, which will use the
+    /// given hasher. Like [`Intern::with_hasher`], the id type may need to be
+    /// spelled out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::{Intern, InternId};
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let intern = Intern::<InternId, _>::with_capacity_and_hasher(10, RandomState::new());
+    /// assert!(intern.capacity() >= 10);
+    /// ```
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        Self {
+            data: HashTable::with_capacity(capacity),
+            list: Vec::with_capacity(capacity),
+            hasher,
+            max_len: None,
+            scratch: String::new(),
+            #[cfg(feature = "stats")]
+            stats: Counters::default(),
         }
+    }
 
-        let owned = input.into().into_boxed_str();
+    /// Returns the number of strings the intern table can hold without reallocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let intern = Intern::with_capacity(10);
+    /// assert!(intern.capacity() >= 10);
+    /// ```
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.list.capacity()
+    }
 
-        let str_data = owned.as_ptr();
-        let str_len = owned.len();
+    /// Reserve capacity for at least `additional` more strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.reserve(10);
+    /// assert!(intern.capacity() >= 10);
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        self.list.reserve(additional);
+        self.data
+            .reserve(additional, rehash(&self.list, &self.hasher));
+    }
 
-        let id = self.list.len() as InternId;
-        self.list.push(owned);
+    /// Try to reserve capacity for at least `additional` more strings.
+    /// Returns an error instead of aborting if the memory cannot be allocated.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::AllocFailed`] if the capacity overflows or the
+    /// allocator reports a failure. The table is left usable, though the list
+    /// may have grown even if the hash table could not.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::{Intern, InternError};
+    ///
+    /// let mut intern = Intern::new();
+    /// assert_eq!(intern.try_reserve(10), Ok(()));
+    /// assert_eq!(intern.try_reserve(usize::MAX), Err(InternError::AllocFailed));
+    /// ```
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), InternError> {
+        self.list
+            .try_reserve(additional)
+            .map_err(|_| InternError::AllocFailed)?;
+        self.data
+            .try_reserve(additional, rehash(&self.list, &self.hasher))
+            .map_err(|_| InternError::AllocFailed)
+    }
 
-        // SAFETY: we can do this because the allocations inside of a Box<str>
-        // are stable, and so passing ownership to push does not change the
-        // address.
-        //
-        // additionally, because we have not touched the string since we created
-        // these raw pointers ourselves, we know that it is valid UTF-8 and so
-        // can skip that check as well.
-        let k =
-            unsafe { std::str::from_utf8_unchecked(std::slice::from_raw_parts(str_data, str_len)) };
+    /// Reserve capacity for exactly `additional` more strings.
+    /// The hash map may still allocate slightly more than requested.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.reserve_exact(10);
+    /// assert!(intern.capacity() >= 10);
+    /// ```
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.list.reserve_exact(additional);
+        self.data
+            .reserve(additional, rehash(&self.list, &self.hasher));
+    }
 
-        self.data.insert(k, id);
-        id
+    /// Shrink the capacity of the intern table as much as possible.
+    ///
+    /// Only the table itself is reallocated; ids and lookups are unaffected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::with_capacity(100);
+    /// let id = intern.intern("hello");
+    /// intern.shrink_to_fit();
+    /// assert!(intern.capacity() < 100);
+    /// assert_eq!(intern.lookup(id), "hello");
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.list.shrink_to_fit();
+        self.data.shrink_to_fit(rehash(&self.list, &self.hasher));
+        self.scratch.shrink_to_fit();
     }
 
-    /// Lookup the interned string by id.
+    /// Limit the length in bytes of strings which may be interned.
+    /// Pass `None` to remove the limit, which is the default.
     ///
-    /// # Panics
+    /// Interning a new string longer than the limit fails with
+    /// [`InternError::TooLong`] before anything is allocated. Strings which are
+    /// already in the table are still found.
     ///
-    /// Panics if the id is not valid.
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::{Intern, InternError};
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.set_max_len(Some(5));
+    /// assert!(intern.try_intern("hello").is_ok());
+    /// assert_eq!(intern.try_intern("hello world"), Err(InternError::TooLong));
+    /// ```
+    pub fn set_max_len(&mut self, max_len: Option<usize>) {
+        self.max_len = max_len;
+    }
+
+    /// Returns the maximum length of strings which may be interned, if limited.
     ///
     /// # Examples
     ///
@@ -82,16 +547,17 @@ impl Intern<'_> {
     /// use intern_string::Intern;
     ///
     /// let mut intern = Intern::new();
-    /// let id = intern.intern("hello");
-    /// assert_eq!(intern.lookup(id), "hello");
+    /// assert_eq!(intern.max_len(), None);
+    /// intern.set_max_len(Some(64));
+    /// assert_eq!(intern.max_len(), Some(64));
     /// ```
     #[inline]
-    pub fn lookup(&self, id: InternId) -> &str {
-        &self.list[id as usize]
+    pub fn max_len(&self) -> Option<usize> {
+        self.max_len
     }
 
-    /// Lookup the interned string by id.
-    /// Returns `None` if the id is not valid.
+    /// Remove every string from the intern table, keeping the allocated capacity.
+    /// All previously returned ids become invalid and new ids start at 0 again.
     ///
     /// # Examples
     ///
@@ -99,38 +565,3267 @@ impl Intern<'_> {
     /// use intern_string::Intern;
     ///
     /// let mut intern = Intern::new();
-    /// let id = intern.intern("hello");
-    /// assert_eq!(intern.try_lookup(id), Some("hello"));
+    /// intern.intern("hello");
+    /// intern.clear();
+    /// assert!(intern.is_empty());
+    /// assert_eq!(intern.intern("world"), 0);
+    /// ```
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.list.clear();
+    }
+
+    /// Remove every string from the intern table, returning them with their ids.
+    /// The table is empty afterwards, even if the iterator is dropped early,
+    /// and new ids start at 0 again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern("hello");
+    /// let strings: Vec<_> = intern.drain().collect();
+    /// assert_eq!(strings, [(0, Box::from("hello"))]);
+    /// assert!(intern.is_empty());
+    /// assert_eq!(intern.intern("world"), 0);
+    /// ```
+    pub fn drain(&mut self) -> impl Iterator<Item = (Sym, Box<str>)> + '_ {
+        // the hash table refers to strings by position, so empty it before
+        // the strings are moved out.
+        self.data.clear();
+        self.list
+            .drain(..)
+            .enumerate()
+            .map(|(id, s)| (Sym::from_usize(id), into_boxed(s)))
+    }
+
+    /// Remember the current state of the intern table, to undo everything
+    /// interned after this point with [`Intern::rollback`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern("fn");
+    /// let checkpoint = intern.checkpoint();
+    /// intern.intern("main");
+    /// intern.rollback(checkpoint);
+    /// assert_eq!(intern.len(), 1);
     /// ```
     #[inline]
-    pub fn try_lookup(&self, id: InternId) -> Option<&str> {
-        self.list.get(id as usize).map(|s| &**s)
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            len: self.list.len(),
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Remove every string interned since the checkpoint was taken.
+    /// Ids handed out after the checkpoint become invalid, and will be given
+    /// to new strings again. Ids from before the checkpoint stay valid.
+    ///
+    /// Nothing happens if the table has fewer strings than at the checkpoint,
+    /// for example because it was cleared in between.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let checkpoint = intern.checkpoint();
+    /// let id = intern.intern("speculative");
+    /// intern.rollback(checkpoint);
+    /// assert!(!intern.contains("speculative"));
+    /// assert_eq!(intern.intern("other"), id);
+    /// ```
+    pub fn rollback(&mut self, checkpoint: Checkpoint) {
+        let Self {
+            data, list, hasher, ..
+        } = self;
+        if checkpoint.len >= list.len() {
+            return;
+        }
+        // the hash table is keyed by string, so each removed string has to be
+        // hashed again to find its id.
+        for (index, s) in list.iter().enumerate().skip(checkpoint.len) {
+            let hash = hasher.hash_one(&**s);
+            if let Ok(entry) = data.find_entry(hash, |id| id.to_usize() == index) {
+                entry.remove();
+            }
+        }
+        list.truncate(checkpoint.len);
+    }
 
-    #[test]
-    fn interns_and_handles_lookup() {
-        let mut interner = Intern::new();
-        let id = interner.intern("hello");
-        assert_eq!(interner.lookup(id), "hello");
-        assert_eq!(interner.try_lookup(id), Some("hello"));
+    /// Remove the most recently interned string, returning it with its id.
+    /// Returns `None` if the table is empty.
+    ///
+    /// The removed id becomes invalid and is given to the next new string, so
+    /// it must not be used anywhere anymore. See [`Intern::checkpoint`] to
+    /// undo more than one string at once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern("let");
+    /// let id = intern.intern("lookahead");
+    /// assert_eq!(intern.pop(), Some((id, Box::from("lookahead"))));
+    /// assert_eq!(intern.get("lookahead"), None);
+    /// assert_eq!(intern.len(), 1);
+    /// ```
+    pub fn pop(&mut self) -> Option<(Sym, Box<str>)> {
+        let s = self.list.pop()?;
+        let index = self.list.len();
+        let hash = self.hasher.hash_one(&*s);
+        if let Ok(entry) = self.data.find_entry(hash, |id| id.to_usize() == index) {
+            entry.remove();
+        }
+        Some((Sym::from_usize(index), into_boxed(s)))
     }
 
-    #[test]
-    fn reallocate() {
-        let mut interner = Intern::with_capacity(1);
-        let id1 = interner.intern("hello");
+    /// Drop every string whose id is not in `live`, and give the remaining
+    /// strings compact ids starting at 0 again.
+    /// Returns how ids were reassigned: `remap[old]` is the new id of the
+    /// string which had id `old`, or `None` if it was dropped.
+    ///
+    /// Surviving strings keep their relative order, and ids may be listed in
+    /// `live` more than once. Every id not looked up through `remap` becomes
+    /// invalid. The capacity of the table is kept, use
+    /// [`Intern::shrink_to_fit`] to release it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any id in `live` is not valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let ids = intern.intern_all(["a", "b", "c"]);
+    /// let remap = intern.gc(&[ids[2]]);
+    /// assert_eq!(remap, [None, None, Some(0)]);
+    /// assert_eq!(intern.lookup(0), "c");
+    /// ```
+    pub fn gc(&mut self, live: &[Sym]) -> Vec<Option<Sym>> {
+        let mut keep = vec![false; self.list.len()];
+        for id in live {
+            keep[id.to_usize()] = true;
+        }
+        self.retain(|id, _| keep[id.to_usize()])
+    }
 
-        // this should reallocate the internal list
-        let id2 = interner.intern("world");
+    /// Drop every string for which `f` returns `false`, and give the
+    /// remaining strings compact ids starting at 0 again.
+    /// Returns how ids were reassigned: `remap[old]` is the new id of the
+    /// string which had id `old`, or `None` if it was dropped.
+    ///
+    /// `f` is called once for every string, in id order. Surviving strings
+    /// keep their relative order, and interning one of them again returns
+    /// its new id. Every id not looked up through `remap` becomes invalid.
+    /// The capacity of the table is kept, use [`Intern::shrink_to_fit`] to
+    /// release it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern_all(["tmp_a", "b", "tmp_c", "d"]);
+    /// let remap = intern.retain(|_, s| !s.starts_with("tmp_"));
+    /// assert_eq!(remap, [None, Some(0), None, Some(1)]);
+    /// assert_eq!(intern.iter().collect::<Vec<_>>(), ["b", "d"]);
+    /// ```
+    pub fn retain<F: FnMut(Sym, &str) -> bool>(&mut self, mut f: F) -> Vec<Option<Sym>> {
+        let mut next = 0;
+        let remap: Vec<_> = self
+            .list
+            .iter()
+            .enumerate()
+            .map(|(index, s)| {
+                f(Sym::from_usize(index), s).then(|| {
+                    next += 1;
+                    Sym::from_usize(next - 1)
+                })
+            })
+            .collect();
 
-        assert_eq!(interner.lookup(id1), "hello");
-        assert_eq!(interner.try_lookup(id1), Some("hello"));
-        assert_eq!(interner.lookup(id2), "world");
-        assert_eq!(interner.try_lookup(id2), Some("world"));
+        let mut index = 0;
+        self.list.retain(|_| {
+            index += 1;
+            remap[index - 1].is_some()
+        });
+        // strings keep their hash, so the surviving ids can be rewritten in
+        // place without rehashing anything.
+        self.data.retain(|id| match remap[id.to_usize()] {
+            Some(new) => {
+                *id = new;
+                true
+            }
+            None => false,
+        });
+        remap
+    }
+
+    /// Intern every string from an iterator.
+    /// Returns the ids in the same order as the input, so repeated strings
+    /// show up as repeated ids.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`Intern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let ids = intern.intern_all("a b a".split(' '));
+    /// assert_eq!(ids, [0, 1, 0]);
+    /// ```
+    pub fn intern_all<I, V>(&mut self, iter: I) -> Vec<Sym>
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<String> + AsRef<str>,
+    {
+        let iter = iter.into_iter();
+        self.reserve_for_input(iter.size_hint().0);
+        let mut ids = Vec::with_capacity(iter.size_hint().0);
+        ids.extend(iter.map(|s| self.intern(s)));
+        ids
+    }
+
+    /// Intern every line read from `reader`.
+    /// Returns the ids in the same order as the lines.
+    ///
+    /// Lines are split like [`BufRead::lines`](std::io::BufRead::lines): the
+    /// trailing `\n` or `\r\n` is not part of the interned string. A single
+    /// buffer is reused for every line, so lines which are already interned
+    /// do not allocate.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from reading, including invalid UTF-8. The lines read
+    /// before the error stay interned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a line could not be interned. See [`Intern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let ids = intern.intern_lines("apple\npear\r\napple".as_bytes()).unwrap();
+    /// assert_eq!(ids, [0, 1, 0]);
+    /// assert_eq!(intern.lookup(1), "pear");
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn intern_lines<R: std::io::BufRead>(
+        &mut self,
+        mut reader: R,
+    ) -> std::io::Result<Vec<Sym>> {
+        let mut ids = Vec::new();
+        let mut line = String::new();
+        while reader.read_line(&mut line)? != 0 {
+            let s = match line.strip_suffix('\n') {
+                Some(s) => s.strip_suffix('\r').unwrap_or(s),
+                None => &line,
+            };
+            ids.push(self.intern(s));
+            line.clear();
+        }
+        Ok(ids)
+    }
+
+    /// Write the interned strings to `writer` in a compact binary format,
+    /// to be read back with [`Intern::read_from`].
+    ///
+    /// The format is the number of strings, then the length and bytes of
+    /// each string in id order, with lengths as little endian `u64`s.
+    /// Requires the `std` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from writing, and an error of kind
+    /// [`InvalidInput`](std::io::ErrorKind::InvalidInput) without writing
+    /// anything if the table has gaps. See [`Intern::has_gaps`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern("hi");
+    /// let mut bytes = Vec::new();
+    /// intern.write_to(&mut bytes).unwrap();
+    /// assert_eq!(bytes, b"\x01\0\0\0\0\0\0\0\x02\0\0\0\0\0\0\0hi");
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn write_to<W: std::io::Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        if self.has_gaps() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "cannot write an intern table with gaps",
+            ));
+        }
+        writer.write_all(&(self.list.len() as u64).to_le_bytes())?;
+        for s in &self.list {
+            writer.write_all(&(s.len() as u64).to_le_bytes())?;
+            writer.write_all(s.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Read an intern table written by [`Intern::write_to`].
+    /// Every string gets back the id it had when it was written.
+    /// Requires the `std` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from reading, and an error of kind
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData) if a string is not
+    /// valid UTF-8, appears twice, or could not be interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern("hello");
+    /// let mut bytes = Vec::new();
+    /// intern.write_to(&mut bytes).unwrap();
+    ///
+    /// let restored: Intern = Intern::read_from(&mut bytes.as_slice()).unwrap();
+    /// assert_eq!(restored.get("hello"), Some(id));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn read_from<R: std::io::Read>(reader: &mut R) -> std::io::Result<Self>
+    where
+        S: Default,
+    {
+        use std::io::{Error, ErrorKind, Read};
+
+        let len = read_len(reader)?;
+        let mut intern = Self::with_hasher(S::default());
+        // the length is untrusted, so do not reserve for all of it up front.
+        intern.reserve(len.min(1024));
+        let mut buf = Vec::new();
+        for expected in 0..len {
+            let n = read_len(reader)?;
+            buf.clear();
+            reader.by_ref().take(n as u64).read_to_end(&mut buf)?;
+            if buf.len() != n {
+                return Err(ErrorKind::UnexpectedEof.into());
+            }
+            let s = core::str::from_utf8(&buf)
+                .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+            let id = intern
+                .try_intern(s)
+                .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+            if id.to_usize() != expected {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "duplicate string in intern table",
+                ));
+            }
+        }
+        Ok(intern)
+    }
+
+    /// Reserve room before interning `len` strings at once.
+    fn reserve_for_input(&mut self, len: usize) {
+        // like the std collections, only reserve for half of the input when
+        // the table is not empty, since some strings are likely duplicates.
+        let additional = if self.is_empty() {
+            len
+        } else {
+            len.div_ceil(2)
+        };
+        self.reserve(additional);
+    }
+
+    /// Intern a string.
+    /// Returns the interned id.
+    /// If the string is already interned, returns the existing id.
+    /// The string is stored in the intern table for the lifetime of the program.
+    /// The id is a 32-bit integer by default, so there can be at most 2^32 unique strings interned.
+    /// If the limit is reached, this function will panic. Use `try_intern` to
+    /// handle this case without panicking.
+    /// The id is guaranteed to be unique for the lifetime of the program.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.lookup(id), "hello");
+    /// ```
+    #[inline]
+    pub fn intern<V: Into<String> + AsRef<str>>(&mut self, input: V) -> Sym {
+        match self.try_intern(input) {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Intern a string slice.
+    /// Returns the interned id.
+    /// If the string is already interned, returns the existing id.
+    ///
+    /// This is [`Intern::intern`] for `&str` alone, for callers which prefer a
+    /// plain signature. The string is only copied if it is new.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`Intern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern_str("hello");
+    /// assert_eq!(intern.intern(String::from("hello")), id);
+    /// ```
+    #[inline]
+    pub fn intern_str(&mut self, input: &str) -> Sym {
+        let hash = self.hasher.hash_one(input);
+        match self.try_intern_hashed(hash, input, owned) {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Intern a string with its leading and trailing whitespace removed.
+    /// Returns the interned id.
+    /// If the trimmed string is already interned, returns the existing id.
+    ///
+    /// Only the trimmed form is stored, so [`Intern::lookup`] returns it.
+    /// Trimming takes a slice of the input, so nothing is allocated unless
+    /// the trimmed string is new.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`Intern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern_trimmed(" foo\t");
+    /// assert_eq!(intern.intern_trimmed("foo"), id);
+    /// assert_eq!(intern.lookup(id), "foo");
+    /// ```
+    #[inline]
+    pub fn intern_trimmed(&mut self, input: &str) -> Sym {
+        self.intern_str(input.trim())
+    }
+
+    /// Intern a string.
+    /// Returns the interned id, or an error if the string could not be interned.
+    /// If the string is already interned, returns the existing id.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::IdSpaceExhausted`] if every id is already in use,
+    /// or [`InternError::TooLong`] if the string is new and longer than the
+    /// maximum length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.try_intern("hello").unwrap();
+    /// assert_eq!(intern.lookup(id), "hello");
+    /// ```
+    #[inline]
+    pub fn try_intern<V: Into<String> + AsRef<str>>(
+        &mut self,
+        input: V,
+    ) -> Result<Sym, InternError> {
+        let hash = self.hasher.hash_one(input.as_ref());
+        self.try_intern_hashed(hash, input, owned)
+    }
+
+    /// Intern a static string without copying it.
+    /// Returns the interned id.
+    /// If the string is already interned, returns the existing id.
+    ///
+    /// The table stores a reference to the string instead of allocating, which
+    /// makes this a good fit for keywords and other strings known up front.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`Intern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern_static("fn");
+    /// assert_eq!(intern.intern("fn"), id);
+    /// assert_eq!(intern.memory_usage().strings_bytes, 0);
+    /// ```
+    #[inline]
+    pub fn intern_static(&mut self, input: &'static str) -> Sym {
+        let hash = self.hasher.hash_one(input);
+        match self.try_intern_hashed(hash, input, Cow::Borrowed) {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Intern a string which outlives the table without copying it.
+    /// Returns the interned id.
+    /// If the string is already interned, returns the existing id.
+    ///
+    /// Like [`Intern::intern_static`], but for any string which lives for
+    /// `'a`, such as source text kept in an arena for the whole compilation.
+    /// Interning only such strings never copies any of them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`Intern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let source = String::from("let x = x + 1;");
+    /// let mut intern = Intern::new();
+    /// let ids: Vec<_> = source
+    ///     .split(' ')
+    ///     .map(|token| intern.intern_borrowed(token))
+    ///     .collect();
+    /// assert_eq!(ids, [0, 1, 2, 1, 3, 4]);
+    /// assert_eq!(intern.memory_usage().strings_bytes, 0);
+    /// ```
+    #[inline]
+    pub fn intern_borrowed(&mut self, input: &'a str) -> Sym {
+        let hash = self.hasher.hash_one(input);
+        match self.try_intern_hashed(hash, input, Cow::Borrowed) {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Intern a single character.
+    /// Returns the interned id.
+    /// If the character is already interned, returns the existing id.
+    ///
+    /// The character is encoded on the stack, so nothing is allocated unless
+    /// it is new, which suits tokenizers seeing the same punctuation over and
+    /// over.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`Intern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let plus = intern.intern_char('+');
+    /// assert_eq!(intern.intern("+"), plus);
+    /// let lambda = intern.intern_char('λ');
+    /// assert_eq!(intern.lookup(lambda), "λ");
+    /// ```
+    #[inline]
+    pub fn intern_char(&mut self, c: char) -> Sym {
+        self.intern(&*c.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Intern a string which may or may not be owned.
+    /// Returns the interned id.
+    /// If the string is already interned, returns the existing id.
+    ///
+    /// Nothing is allocated if the string is already interned. Otherwise an
+    /// owned string is moved into the table, reusing its buffer unless it has
+    /// excess capacity, and a borrowed string is copied once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`Intern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    /// use std::borrow::Cow;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern_cow(Cow::Owned(String::from("hello")));
+    /// assert_eq!(intern.intern_cow(Cow::Borrowed("hello")), id);
+    /// ```
+    #[inline]
+    pub fn intern_cow(&mut self, input: Cow<'_, str>) -> Sym {
+        self.intern(input)
+    }
+
+    /// Intern an owned string.
+    /// Returns the interned id.
+    /// If the string is already interned, returns the existing id.
+    ///
+    /// This never allocates a string. If the string is already interned, it
+    /// is dropped. Otherwise it is moved into the table as is, keeping any
+    /// excess capacity, where `intern` would shrink it to fit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`Intern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let s = String::with_capacity(16) + "hello";
+    /// let ptr = s.as_ptr();
+    /// let id = intern.intern_owned(s);
+    /// assert_eq!(intern.lookup(id).as_ptr(), ptr);
+    /// ```
+    #[inline]
+    pub fn intern_owned(&mut self, input: String) -> Sym {
+        let hash = self.hasher.hash_one(input.as_str());
+        match self.try_intern_hashed(hash, input, Cow::Owned) {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Intern a boxed string.
+    /// Returns the interned id.
+    /// If the string is already interned, returns the existing id.
+    ///
+    /// This never allocates a string. If the string is already interned, it
+    /// is dropped. Otherwise its allocation is moved into the table as is,
+    /// which makes this the cheapest way to intern strings taken out of
+    /// another table, for example with [`Intern::into_strings`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`Intern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let s: Box<str> = Box::from("hello");
+    /// let ptr = s.as_ptr();
+    /// let id = intern.intern_boxed(s);
+    /// assert_eq!(intern.lookup(id).as_ptr(), ptr);
+    /// ```
+    #[inline]
+    pub fn intern_boxed(&mut self, input: Box<str>) -> Sym {
+        let hash = self.hasher.hash_one(&*input);
+        match self.try_intern_hashed(hash, input, |s| Cow::Owned(s.into_string())) {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Intern a string only if it is at most `max_len` bytes long.
+    /// Returns the interned id, or gives the string back unchanged if it is
+    /// longer.
+    ///
+    /// `Err` is not a failure: it is how long strings, which are unlikely to
+    /// repeat, skip the table so they do not take up room in it. The string
+    /// is returned without being copied.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`Intern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// assert_eq!(intern.intern_or_passthrough(String::from("id"), 8), Ok(0));
+    /// let blob = "x".repeat(100);
+    /// assert_eq!(intern.intern_or_passthrough(blob.clone(), 8), Err(blob));
+    /// assert_eq!(intern.len(), 1);
+    /// ```
+    #[inline]
+    pub fn intern_or_passthrough(&mut self, input: String, max_len: usize) -> Result<Sym, String> {
+        if input.len() > max_len {
+            return Err(input);
+        }
+        Ok(self.intern(input))
+    }
+
+    /// Intern a string under the given id, for example to restore the ids of
+    /// a table exported elsewhere.
+    /// Succeeds without changes if the string already has this id.
+    ///
+    /// If the id is past the end of the table, the ids in between are left as
+    /// gaps, each taking up a slot in the table. A gap holds an empty string
+    /// which is not interned: `lookup` and `try_lookup` return `""` for it,
+    /// but `get("")` does not find it. Gaps count towards `len`, show up in
+    /// `iter` and are removed by `pop` like strings, but are left out of
+    /// `sorted_ids`, `length_histogram` and the number of unique strings in
+    /// the stats. A gap can be filled later with another `intern_at`, while
+    /// `intern` always appends new strings after the last id. A table with
+    /// gaps cannot be written out, see [`Intern::has_gaps`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::IdConflict`] if the id is taken by a different
+    /// string, or the string is already interned under a different id, and
+    /// [`InternError::TooLong`] if the string is longer than the maximum
+    /// length of the table. Returns [`InternError::AllocFailed`] instead of
+    /// aborting if the slots up to the id cannot be allocated, and
+    /// [`InternError::IdSpaceExhausted`] if their number overflows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::{Intern, InternError};
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern_at(2, "two").unwrap();
+    /// assert_eq!(intern.len(), 3);
+    /// assert_eq!(intern.lookup(1), "");
+    /// intern.intern_at(0, "zero").unwrap();
+    /// assert_eq!(intern.intern_at(0, "other"), Err(InternError::IdConflict));
+    /// assert_eq!(intern.intern("three"), 3);
+    /// ```
+    pub fn intern_at<V: Into<String> + AsRef<str>>(
+        &mut self,
+        id: Sym,
+        input: V,
+    ) -> Result<(), InternError> {
+        let hash = self.hasher.hash_one(input.as_ref());
+        if let Some(existing) = self.find(hash, input.as_ref()) {
+            return if existing == id {
+                Ok(())
+            } else {
+                Err(InternError::IdConflict)
+            };
+        }
+        let index = id.to_usize();
+        if index < self.list.len() && !self.is_gap(index) {
+            return Err(InternError::IdConflict);
+        }
+        check_len(index, input.as_ref(), self.max_len, Sym::MAX)?;
+
+        self.data
+            .try_reserve(1, rehash(&self.list, &self.hasher))
+            .map_err(|_| InternError::AllocFailed)?;
+        if index >= self.list.len() {
+            let len = index.checked_add(1).ok_or(InternError::IdSpaceExhausted)?;
+            self.list
+                .try_reserve_exact(len - self.list.len())
+                .map_err(|_| InternError::AllocFailed)?;
+            self.list.resize(len, Cow::Borrowed(""));
+        }
+        self.list[index] = owned(input);
+        self.data
+            .insert_unique(hash, id, rehash(&self.list, &self.hasher));
+        Ok(())
+    }
+
+    /// Returns `true` if [`Intern::intern_at`] has left any gaps which are not
+    /// filled yet.
+    ///
+    /// The serialized forms of a table only hold its strings, which cannot
+    /// tell a gap from an interned empty string. So a table with gaps is not
+    /// written out by [`Intern::write_to`] or the `serde`, `borsh` and `rkyv`
+    /// implementations, which return an error instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern_at(1, "one").unwrap();
+    /// assert!(intern.has_gaps());
+    /// intern.intern_at(0, "zero").unwrap();
+    /// assert!(!intern.has_gaps());
+    /// ```
+    #[inline]
+    pub fn has_gaps(&self) -> bool {
+        // every string but a gap is indexed exactly once.
+        self.data.len() < self.list.len()
+    }
+
+    /// Check whether the string at `index` is a gap left by `intern_at`,
+    /// rather than an interned string.
+    fn is_gap(&self, index: usize) -> bool {
+        let s = &*self.list[index];
+        s.is_empty()
+            && self
+                .data
+                .find(self.hasher.hash_one(s), |id| id.to_usize() == index)
+                .is_none()
+    }
+
+    /// Intern a string whose hash has already been computed.
+    /// `store` is only called if the string is new, to turn it into the
+    /// string which is kept in the table.
+    #[inline]
+    fn try_intern_hashed<V: AsRef<str>>(
+        &mut self,
+        hash: u64,
+        input: V,
+        store: impl FnOnce(V) -> Cow<'a, str>,
+    ) -> Result<Sym, InternError> {
+        // most strings are interned many times, so look for an existing id
+        // first. Unlike `entry`, `find` never needs to make room in the table.
+        if let Some(id) = self.find(hash, input.as_ref()) {
+            #[cfg(feature = "stats")]
+            {
+                self.stats.hits += 1;
+                self.stats.bytes_saved += input.as_ref().len() as u64;
+            }
+            return Ok(id);
+        }
+        let Self {
+            data,
+            list,
+            hasher,
+            max_len,
+            ..
+        } = self;
+        check_len(list.len(), input.as_ref(), *max_len, Sym::MAX)?;
+        let id = Sym::from_usize(list.len());
+        // store the string before indexing it, so that a panic in `store`
+        // leaves no id without a string behind.
+        list.push(store(input));
+        // the string is known to be new, so the slot is found without
+        // comparing any strings. Growing only rehashes the existing ids.
+        data.insert_unique(hash, id, rehash(list, hasher));
+        #[cfg(feature = "stats")]
+        {
+            self.stats.misses += 1;
+        }
+        Ok(id)
+    }
+
+    /// Hash a string with the hasher used by this intern table.
+    /// The result can be passed to `intern_with_hash` and `get_with_hash`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let intern = Intern::new();
+    /// assert_eq!(intern.hash("hello"), intern.hash("hello"));
+    /// ```
+    #[inline]
+    pub fn hash(&self, input: &str) -> u64 {
+        self.hasher.hash_one(input)
+    }
+
+    /// Intern a string using a precomputed hash.
+    ///
+    /// The hash must have been computed with [`Intern::hash`], or with the same
+    /// `BuildHasher` as this table. Passing any other hash will not cause
+    /// undefined behavior, but the string may be interned twice under
+    /// different ids, or not be found again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned, like `intern`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let hash = intern.hash("hello");
+    /// let id = intern.intern_with_hash("hello", hash);
+    /// assert_eq!(intern.intern("hello"), id);
+    /// ```
+    #[inline]
+    pub fn intern_with_hash(&mut self, input: &str, hash: u64) -> Sym {
+        match self.try_intern_hashed(hash, input, owned) {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Get the id of an already interned string using a precomputed hash.
+    /// Returns `None` if the string has not been interned.
+    ///
+    /// The hash must have been computed like for [`Intern::intern_with_hash`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.get_with_hash("hello", intern.hash("hello")), Some(id));
+    /// ```
+    #[inline]
+    pub fn get_with_hash(&self, input: &str, hash: u64) -> Option<Sym> {
+        self.find(hash, input)
+    }
+
+    /// Intern a string which is only built if it is not interned yet.
+    /// Returns the interned id.
+    /// If `key` is already interned, returns the existing id without calling `make`.
+    ///
+    /// `make` must return a string equal to `key`, otherwise the table can no
+    /// longer find the string. This is checked in debug builds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`Intern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.get_or_intern_with("hello", || String::from("hello"));
+    /// let again = intern.get_or_intern_with("hello", || unreachable!());
+    /// assert_eq!(again, id);
+    /// ```
+    #[inline]
+    pub fn get_or_intern_with<F: FnOnce() -> String>(&mut self, key: &str, make: F) -> Sym {
+        let hash = self.hasher.hash_one(key);
+        let store = |key: &str| {
+            let s = make();
+            debug_assert_eq!(s, key, "`make` must return a string equal to the key");
+            owned(s)
+        };
+        match self.try_intern_hashed(hash, key, store) {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Intern a string, like `HashSet::insert`.
+    /// Returns the id and whether the string was newly interned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned, like `intern`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// assert_eq!(intern.insert("hello"), (0, true));
+    /// assert_eq!(intern.insert("hello"), (0, false));
+    /// ```
+    #[inline]
+    pub fn insert<V: Into<String> + AsRef<str>>(&mut self, input: V) -> (Sym, bool) {
+        let len = self.list.len();
+        let id = self.intern(input);
+        (id, self.list.len() > len)
+    }
+
+    /// Intern a string, returning both its id and the interned copy.
+    /// This saves a separate `lookup` when the stored string is needed right away.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned, like `intern`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let (id, s) = intern.get_or_intern(String::from("hello"));
+    /// assert_eq!(s, "hello");
+    /// assert_eq!(intern.get_or_intern("hello"), (id, "hello"));
+    /// ```
+    #[inline]
+    pub fn get_or_intern<V: Into<String> + AsRef<str>>(&mut self, input: V) -> (Sym, &str) {
+        let id = self.intern(input);
+        (id, &self.list[id.to_usize()])
+    }
+
+    /// Get the id of an already interned string.
+    /// Returns `None` if the string has not been interned.
+    /// Unlike `intern`, this never inserts into the table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.get("hello"), Some(id));
+    /// assert_eq!(intern.get("world"), None);
+    /// ```
+    #[inline]
+    pub fn get(&self, input: &str) -> Option<Sym> {
+        self.find(self.hash(input), input)
+    }
+
+    /// Get the id of an already interned string, accepting the same inputs as `intern`.
+    /// Returns `None` if the string has not been interned.
+    /// This never allocates or inserts into the table, even when given an owned `String`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.try_intern_existing(String::from("hello")), Some(id));
+    /// assert_eq!(intern.try_intern_existing("world"), None);
+    /// assert_eq!(intern.len(), 1);
+    /// ```
+    #[inline]
+    pub fn try_intern_existing<V: AsRef<str>>(&self, input: V) -> Option<Sym> {
+        self.get(input.as_ref())
+    }
+
+    /// Find the id of `input` in `data`, given its hash.
+    #[inline]
+    fn find(&self, hash: u64, input: &str) -> Option<Sym> {
+        self.data
+            .find(hash, |id| *self.list[id.to_usize()] == *input)
+            .copied()
+    }
+
+    /// Check whether a string has already been interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern("hello");
+    /// assert!(intern.contains("hello"));
+    /// assert!(!intern.contains("world"));
+    /// ```
+    #[inline]
+    pub fn contains(&self, input: &str) -> bool {
+        self.get(input).is_some()
+    }
+
+    /// Check whether an id refers to an interned string.
+    /// If it does, `lookup` will not panic for it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern("hello");
+    /// assert!(intern.contains_id(id));
+    /// assert!(!intern.contains_id(id + 1));
+    /// ```
+    #[inline]
+    pub fn contains_id(&self, id: Sym) -> bool {
+        id.to_usize() < self.list.len()
+    }
+
+    /// Returns the length in bytes of the interned string of an id.
+    /// Returns `None` if the id is not valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern("héllo");
+    /// assert_eq!(intern.byte_len(id), Some(6));
+    /// assert_eq!(intern.byte_len(id + 1), None);
+    /// ```
+    #[inline]
+    pub fn byte_len(&self, id: Sym) -> Option<usize> {
+        self.list.get(id.to_usize()).map(|s| s.len())
+    }
+
+    /// Returns the total length in bytes of all interned strings.
+    /// Unlike [`Intern::memory_usage`], this counts every string by its
+    /// length, however it is stored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern("hello");
+    /// intern.intern_static("world");
+    /// intern.intern("hello");
+    /// assert_eq!(intern.total_bytes(), 10);
+    /// ```
+    pub fn total_bytes(&self) -> usize {
+        self.list.iter().map(|s| s.len()).sum()
+    }
+
+    /// Check that the ids and the strings of the table agree.
+    /// Every string must be found under its own id, and every id must refer
+    /// to a string. Meant for testing code which changes the table.
+    ///
+    /// The empty gaps left by [`Intern::intern_at`] are not interned, and are
+    /// allowed. This rehashes every string, so it takes O(n) time.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first inconsistency found. See [`ConsistencyError`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern_all(["a", "b", "c"]);
+    /// intern.retain(|_, s| s != "b");
+    /// assert_eq!(intern.validate(), Ok(()));
+    /// ```
+    pub fn validate(&self) -> Result<(), ConsistencyError> {
+        if let Some(id) = self.data.iter().find(|id| id.to_usize() >= self.list.len()) {
+            return Err(ConsistencyError::DanglingId(id.to_usize()));
+        }
+        let mut indexed = 0;
+        for (index, s) in self.list.iter().enumerate() {
+            match self.find(self.hasher.hash_one(&**s), s) {
+                Some(id) if id.to_usize() == index => indexed += 1,
+                _ if self.is_gap(index) => {}
+                Some(_) => return Err(ConsistencyError::DuplicateString(index)),
+                None => return Err(ConsistencyError::MissingEntry(index)),
+            }
+        }
+        if self.data.len() != indexed {
+            return Err(ConsistencyError::ExtraEntries);
+        }
+        Ok(())
+    }
+
+    /// Check whether two ids refer to the same string.
+    ///
+    /// Every string is interned only once, so two ids from the same table are
+    /// equal exactly when their strings are. Comparing ids is all this does,
+    /// and is why interned strings are cheap to compare. Ids from different
+    /// tables cannot be compared this way. See [`Intern::debug_same`] to also
+    /// check the strings in debug builds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let a = intern.intern("hello");
+    /// let b = intern.intern(String::from("hello"));
+    /// let c = intern.intern("world");
+    /// assert!(intern.same(a, b));
+    /// assert!(!intern.same(a, c));
+    /// ```
+    #[inline]
+    pub fn same(&self, a: Sym, b: Sym) -> bool {
+        a == b
+    }
+
+    /// Check whether two ids refer to the same string, like [`Intern::same`],
+    /// and in debug builds also check that their strings agree.
+    ///
+    /// This catches ids from another table, or ids which were not remapped
+    /// after the table changed. Release builds only compare the ids.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if either id is not valid, or if the ids and
+    /// their strings do not agree on whether they are the same. The gaps left
+    /// by [`Intern::intern_at`] all hold the empty string, so two of them do
+    /// not agree either.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let a = intern.intern("hello");
+    /// let b = intern.intern("world");
+    /// assert!(intern.debug_same(a, a));
+    /// assert!(!intern.debug_same(a, b));
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn debug_same(&self, a: Sym, b: Sym) -> bool {
+        debug_assert_eq!(
+            a == b,
+            self.lookup(a) == self.lookup(b),
+            "ids {a:?} and {b:?} disagree with their strings"
+        );
+        a == b
+    }
+
+    /// Lookup the interned string by id.
+    ///
+    /// The returned string borrows the table, not the call, so any number of
+    /// looked up strings can be held at once, alongside other calls which
+    /// take `&self`. Only interning more strings, which takes `&mut self`,
+    /// has to wait until they are no longer used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid, with a message naming the id and the
+    /// number of interned strings, to help spot ids from another table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let hello = intern.intern("hello");
+    /// let world = intern.intern("world");
+    ///
+    /// let a = intern.lookup(hello);
+    /// let b = intern.lookup(world);
+    /// assert_eq!(intern.get(b), Some(world));
+    /// assert_eq!([a, b].join(" "), "hello world");
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn lookup(&self, id: Sym) -> &str {
+        match self.list.get(id.to_usize()) {
+            Some(s) => s,
+            None => invalid_id(id.to_usize(), self.list.len()),
+        }
+    }
+
+    /// Lookup the interned string by id.
+    /// Returns `None` if the id is not valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.try_lookup(id), Some("hello"));
+    /// ```
+    #[inline]
+    pub fn try_lookup(&self, id: Sym) -> Option<&str> {
+        self.list.get(id.to_usize()).map(|s| &**s)
+    }
+
+    /// Lookup the interned strings of several ids.
+    /// Yields `None` for each id which is not valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let hello = intern.intern("hello");
+    /// let ids = [hello, 7, hello];
+    /// let strings: Vec<_> = intern.try_lookup_many(&ids).collect();
+    /// assert_eq!(strings, [Some("hello"), None, Some("hello")]);
+    /// assert!(!intern.try_lookup_many(&ids).all(|s| s.is_some()));
+    /// ```
+    #[inline]
+    pub fn try_lookup_many<'s>(
+        &'s self,
+        ids: &'s [Sym],
+    ) -> impl Iterator<Item = Option<&'s str>> + 's {
+        ids.iter().map(|&id| self.try_lookup(id))
+    }
+
+    /// Lookup the interned string by id, without checking that the id is valid.
+    ///
+    /// # Safety
+    ///
+    /// The id must have been returned by this intern table, and must not have
+    /// been invalidated since, for example by [`Intern::clear`],
+    /// [`Intern::rollback`] or [`Intern::retain`]. In other words, the id has
+    /// to be less than [`Intern::len`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern("hello");
+    /// // SAFETY: `id` was just returned by `intern`.
+    /// assert_eq!(unsafe { intern.lookup_unchecked(id) }, "hello");
+    /// ```
+    #[inline]
+    pub unsafe fn lookup_unchecked(&self, id: Sym) -> &str {
+        debug_assert!(id.to_usize() < self.list.len());
+        // SAFETY: the caller guarantees that the id is in bounds.
+        unsafe { self.list.get_unchecked(id.to_usize()) }
+    }
+
+    /// Lookup the interned strings of many ids at once.
+    /// Returns the strings in the same order as the ids.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any id is not valid. See [`Intern::try_resolve_all`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let ids = intern.intern_all(["a", "b", "a"]);
+    /// assert_eq!(intern.resolve_all(&ids), ["a", "b", "a"]);
+    /// ```
+    pub fn resolve_all(&self, ids: &[Sym]) -> Vec<&str> {
+        ids.iter().map(|&id| self.lookup(id)).collect()
+    }
+
+    /// Lookup the interned strings of many ids at once.
+    /// Invalid ids map to `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern("a");
+    /// assert_eq!(intern.try_resolve_all(&[id, 7]), [Some("a"), None]);
+    /// ```
+    pub fn try_resolve_all(&self, ids: &[Sym]) -> Vec<Option<&str>> {
+        ids.iter().map(|&id| self.try_lookup(id)).collect()
+    }
+
+    /// Returns the number of unique strings in the intern table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern("hello");
+    /// intern.intern("hello");
+    /// intern.intern("world");
+    /// assert_eq!(intern.len(), 2);
+    /// ```
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns `true` if no strings have been interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// assert!(intern.is_empty());
+    /// intern.intern("hello");
+    /// assert!(!intern.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Returns how many more unique strings can be interned before every id
+    /// is in use and interning fails with [`InternError::IdSpaceExhausted`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::<u16>::default();
+    /// assert_eq!(intern.remaining_ids(), 1 << 16);
+    /// intern.intern("hello");
+    /// assert_eq!(intern.remaining_ids(), (1 << 16) - 1);
+    /// ```
+    #[inline]
+    pub fn remaining_ids(&self) -> u64 {
+        // the number of ids is one more than the largest id, which needs care
+        // for ids as wide as `u64`.
+        Sym::MAX
+            .checked_sub(self.list.len())
+            .map_or(0, |n| (n as u64).saturating_add(1))
+    }
+
+    /// Returns all interned strings as a slice, in id order.
+    /// The string with id `id` is at index `id`, so `slice[id]` equals `lookup(id)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern("hello");
+    /// intern.intern_static("world");
+    /// let strings = intern.as_slice();
+    /// assert_eq!(strings, ["hello", "world"]);
+    /// assert_eq!(strings[id as usize], intern.lookup(id));
+    /// ```
+    #[inline]
+    pub fn as_slice(&self) -> &[Cow<'a, str>] {
+        &self.list
+    }
+
+    /// Iterate over the interned strings which start with `prefix`, with
+    /// their ids, in id order.
+    ///
+    /// This scans every string in the table, so it takes O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern_all(["print", "parse", "println"]);
+    /// let ids: Vec<_> = intern.with_prefix("pri").map(|(id, _)| id).collect();
+    /// assert_eq!(ids, [0, 2]);
+    /// ```
+    pub fn with_prefix<'s>(&'s self, prefix: &'s str) -> impl Iterator<Item = (Sym, &'s str)> + 's {
+        self.iter_with_ids()
+            .filter(move |(_, s)| s.starts_with(prefix))
+    }
+
+    /// Iterate over the ids of the interned strings which match a predicate,
+    /// in id order.
+    ///
+    /// This scans every string in the table, so it takes O(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern_all(["foo", "+", "bar_2", "1x"]);
+    /// let is_ident = |s: &str| s.starts_with(|c: char| c.is_alphabetic() || c == '_');
+    /// let ids: Vec<_> = intern.find_ids(is_ident).collect();
+    /// assert_eq!(ids, [0, 2]);
+    /// ```
+    pub fn find_ids<'s, F: FnMut(&str) -> bool + 's>(
+        &'s self,
+        mut pred: F,
+    ) -> impl Iterator<Item = Sym> + 's {
+        self.iter_with_ids()
+            .filter_map(move |(id, s)| pred(s).then_some(id))
+    }
+
+    /// Returns every id, sorted by the string it refers to.
+    ///
+    /// Strings are compared byte by byte, like `str`'s `Ord`. The gaps left
+    /// by [`Intern::intern_at`] are skipped. Sorting takes O(n log n) time and
+    /// allocates the returned list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern_all(["pear", "apple", "fig"]);
+    /// assert_eq!(intern.sorted_ids(), [1, 2, 0]);
+    /// ```
+    pub fn sorted_ids(&self) -> Vec<Sym> {
+        let mut ids: Vec<Sym> = self
+            .ids()
+            .filter(|id| !self.is_gap(id.to_usize()))
+            .collect();
+        ids.sort_unstable_by(|&a, &b| self.list[a.to_usize()].cmp(&self.list[b.to_usize()]));
+        ids
+    }
+
+    /// Iterate over all interned strings with their ids, sorted by string.
+    /// See [`Intern::sorted_ids`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern_all(["pear", "apple"]);
+    /// let sorted: Vec<_> = intern.sorted_iter().collect();
+    /// assert_eq!(sorted, [(1, "apple"), (0, "pear")]);
+    /// ```
+    pub fn sorted_iter(&self) -> impl Iterator<Item = (Sym, &str)> {
+        self.sorted_ids()
+            .into_iter()
+            .map(|id| (id, &*self.list[id.to_usize()]))
+    }
+
+    /// Iterate over all interned strings in insertion order.
+    /// The position of each string in the iteration is its id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern("hello");
+    /// intern.intern("world");
+    /// assert_eq!(intern.iter().collect::<Vec<_>>(), ["hello", "world"]);
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.list.iter().map(|s| &**s)
+    }
+
+    /// Iterate over all interned strings together with their ids.
+    /// Ids are yielded in ascending order, matching insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let hello = intern.intern("hello");
+    /// let world = intern.intern("world");
+    /// assert_eq!(
+    ///     intern.iter_with_ids().collect::<Vec<_>>(),
+    ///     [(hello, "hello"), (world, "world")]
+    /// );
+    /// ```
+    #[inline]
+    pub fn iter_with_ids(&self) -> Iter<'_, Sym> {
+        Iter::new(&self.list)
+    }
+
+    /// Iterate over every valid id in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let intern = Intern::from_sorted(["a", "bc"]);
+    /// let lens: Vec<usize> = intern.ids().map(|id| intern.lookup(id).len()).collect();
+    /// assert_eq!(lens, [1, 2]);
+    /// assert_eq!(intern.ids().len(), 2);
+    /// ```
+    #[inline]
+    pub fn ids(&self) -> impl ExactSizeIterator<Item = Sym> + DoubleEndedIterator {
+        (0..self.list.len()).map(Sym::from_usize)
+    }
+
+    /// Returns the first id, or `None` if the table is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// assert_eq!(intern.first_id(), None);
+    /// intern.intern("hello");
+    /// assert_eq!(intern.first_id(), Some(0));
+    /// ```
+    #[inline]
+    pub fn first_id(&self) -> Option<Sym> {
+        self.ids().next()
+    }
+
+    /// Returns the most recently assigned id, or `None` if the table is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// assert_eq!(intern.last_id(), None);
+    /// intern.intern("hello");
+    /// let id = intern.intern("world");
+    /// assert_eq!(intern.last_id(), Some(id));
+    /// ```
+    #[inline]
+    pub fn last_id(&self) -> Option<Sym> {
+        self.ids().next_back()
+    }
+
+    /// Estimate the heap memory used by the intern table.
+    /// Strings which are stored by reference do not count towards `strings_bytes`,
+    /// while strings with excess capacity count with their full capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern("hello");
+    /// let usage = intern.memory_usage();
+    /// assert_eq!(usage.strings_bytes, 5);
+    /// assert!(usage.total() > 5);
+    /// ```
+    pub fn memory_usage(&self) -> MemoryUsage {
+        MemoryUsage {
+            strings_bytes: self
+                .list
+                .iter()
+                .filter_map(|s| match s {
+                    Cow::Owned(s) => Some(s.capacity()),
+                    Cow::Borrowed(_) => None,
+                })
+                .sum(),
+            list_overhead: self.list.capacity() * core::mem::size_of::<Cow<'a, str>>(),
+            map_overhead: self.data.allocation_size(),
+        }
+    }
+
+    /// Count the interned strings of each length in bytes.
+    /// Index `i` of the result holds the number of strings of length `i`.
+    ///
+    /// Strings of 64 bytes or more are all counted in bucket 64, so the result
+    /// has at most 65 buckets. It ends at the longest string, and is empty if
+    /// no strings have been interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// for s in ["a", "b", "abc", &"x".repeat(100)] {
+    ///     intern.intern(s);
+    /// }
+    /// let histogram = intern.length_histogram();
+    /// assert_eq!(histogram[1], 2);
+    /// assert_eq!(histogram[3], 1);
+    /// assert_eq!(histogram[64], 1);
+    /// ```
+    pub fn length_histogram(&self) -> Vec<usize> {
+        const LAST_BUCKET: usize = 64;
+        let mut histogram = Vec::new();
+        for (index, s) in self.list.iter().enumerate() {
+            if self.is_gap(index) {
+                continue;
+            }
+            let bucket = s.len().min(LAST_BUCKET);
+            if bucket >= histogram.len() {
+                histogram.resize(bucket + 1, 0);
+            }
+            histogram[bucket] += 1;
+        }
+        histogram
+    }
+
+    /// Intern every string of `other` into this table.
+    /// Returns a table which maps every id of `other` to its id in this table,
+    /// so that `remap[old_id] == new_id`.
+    ///
+    /// Ids are not preserved across a merge: strings which are already
+    /// interned keep their existing ids here, and new strings get fresh ids.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned, like `intern`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut a = Intern::new();
+    /// a.intern("hello");
+    ///
+    /// let mut b = Intern::new();
+    /// let world = b.intern("world");
+    /// let hello = b.intern("hello");
+    ///
+    /// let remap = a.merge(&b);
+    /// assert_eq!(remap, [1, 0]);
+    /// assert_eq!(a.lookup(remap[world as usize]), "world");
+    /// assert_eq!(a.lookup(remap[hello as usize]), "hello");
+    /// ```
+    pub fn merge<S2>(&mut self, other: &Intern<'_, Sym, S2>) -> Vec<Sym> {
+        self.reserve(other.list.len());
+        other.list.iter().map(|s| self.intern(&**s)).collect()
+    }
+
+    /// Intern every string of another intern table, in id order.
+    /// Like [`Intern::merge`], but without building a list of the new ids,
+    /// for when only the strings matter. The tables need not share an id type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned, like `intern`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut a = Intern::new();
+    /// a.intern("hello");
+    ///
+    /// let mut b = Intern::<u16>::default();
+    /// b.intern("world");
+    /// b.intern("hello");
+    ///
+    /// a.extend_from_interner(&b);
+    /// assert_eq!(a.iter().collect::<Vec<_>>(), ["hello", "world"]);
+    /// ```
+    pub fn extend_from_interner<Sym2, S2>(&mut self, other: &Intern<'_, Sym2, S2>) {
+        self.reserve_for_input(other.list.len());
+        for s in &other.list {
+            self.intern(&**s);
+        }
+    }
+
+    /// Build a map from every interned string to its id.
+    /// The map borrows the strings from the table and uses a clone of its
+    /// hasher.
+    ///
+    /// This copies the contents of the table, so it only pays off when the
+    /// map is needed as a standard `HashMap`, for example to hand it to code
+    /// which expects one. [`Intern::get`] is just as fast for plain lookups.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern("hello");
+    /// let map = intern.to_lookup_map();
+    /// assert_eq!(map["hello"], id);
+    /// assert_eq!(map.get("world"), None);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn to_lookup_map(&self) -> std::collections::HashMap<&str, Sym, S>
+    where
+        S: Clone,
+    {
+        let mut map =
+            std::collections::HashMap::with_capacity_and_hasher(self.len(), self.hasher.clone());
+        map.extend(self.iter_with_ids().map(|(id, s)| (s, id)));
+        map
+    }
+
+    /// Consume the intern table, returning the interned strings.
+    /// The strings are in id order, so the index of each string is its id.
+    ///
+    /// Strings which were copied into the table are returned without copying
+    /// them again, while strings stored by reference are copied into a new
+    /// allocation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern("hello");
+    /// intern.intern("world");
+    /// assert_eq!(intern.into_strings(), ["hello".into(), "world".into()]);
+    /// ```
+    pub fn into_strings(self) -> Vec<Box<str>> {
+        self.list.into_iter().map(into_boxed).collect()
+    }
+
+    /// Convert the intern table to one with `u16` ids, to save memory in
+    /// structures which store many ids.
+    /// Returns the table unchanged as an error if it holds more strings than
+    /// `u16` has ids for.
+    ///
+    /// Every string keeps its id, so ids can be converted with `as u16`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let id = intern.intern("hello");
+    ///
+    /// let narrow = intern.into_narrow().unwrap();
+    /// assert_eq!(narrow.get("hello"), Some(id as u16));
+    /// assert_eq!(narrow.lookup(id as u16), "hello");
+    /// ```
+    pub fn into_narrow(self) -> Result<Intern<'a, u16, S>, Self> {
+        if self.list.len() > u16::MAX as usize + 1 {
+            return Err(self);
+        }
+        let Self {
+            data: old,
+            list,
+            hasher,
+            max_len,
+            scratch,
+            #[cfg(feature = "stats")]
+            stats,
+        } = self;
+        let mut data = HashTable::with_capacity(old.len());
+        for id in old {
+            let index = id.to_usize();
+            let hash = hasher.hash_one(&*list[index]);
+            data.insert_unique(hash, index as u16, rehash(&list, &hasher));
+        }
+        Ok(Intern {
+            data,
+            list,
+            hasher,
+            max_len,
+            scratch,
+            #[cfg(feature = "stats")]
+            stats,
+        })
+    }
+
+    /// Returns how often strings were found or newly inserted while interning.
+    ///
+    /// Every successful call to one of the `intern` methods or to
+    /// `get_or_intern` is counted, while lookups through `get` and the entry
+    /// API are not. The counters are kept when the table is cleared.
+    /// Requires the `stats` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// for word in ["a", "b", "a", "a"] {
+    ///     intern.intern(word);
+    /// }
+    /// let stats = intern.stats();
+    /// assert_eq!((stats.hits, stats.misses, stats.unique), (2, 2, 2));
+    /// assert_eq!(stats.hit_rate(), 0.5);
+    /// assert_eq!(stats.bytes_saved, 2);
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> InternStats {
+        InternStats {
+            hits: self.stats.hits,
+            misses: self.stats.misses,
+            bytes_saved: self.stats.bytes_saved,
+            unique: self.data.len(),
+        }
+    }
+}
+
+impl<Sym, S: Default> Default for Intern<'_, Sym, S> {
+    fn default() -> Self {
+        Self {
+            data: HashTable::new(),
+            list: Vec::new(),
+            hasher: S::default(),
+            max_len: None,
+            scratch: String::new(),
+            #[cfg(feature = "stats")]
+            stats: Counters::default(),
+        }
+    }
+}
+
+impl<Sym: Clone, S: Clone> Clone for Intern<'_, Sym, S> {
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+            list: self.list.clone(),
+            hasher: self.hasher.clone(),
+            max_len: self.max_len,
+            scratch: String::new(),
+            #[cfg(feature = "stats")]
+            stats: self.stats,
+        }
+    }
+}
+
+impl<Sym, S> PartialEq for Intern<'_, Sym, S> {
+    /// Two intern tables are equal if they hold the same strings under the same ids.
+    fn eq(&self, other: &Self) -> bool {
+        self.list == other.list
+    }
+}
+
+impl<Sym, S> Eq for Intern<'_, Sym, S> {}
+
+impl<Sym, S> Hash for Intern<'_, Sym, S> {
+    /// Hashes the strings in id order, so the hash depends on both which
+    /// strings are interned and which ids they were given, like `PartialEq`.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.list.hash(state);
+    }
+}
+
+impl<V, Sym, S> FromIterator<V> for Intern<'_, Sym, S>
+where
+    V: Into<String> + AsRef<str>,
+    Sym: Symbol,
+    S: BuildHasher + Default,
+{
+    /// Build an intern table from an iterator of strings.
+    /// Repeated strings collapse into a single id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let intern: Intern = ["hello", "world", "hello"].into_iter().collect();
+    /// assert_eq!(intern.len(), 2);
+    /// assert_eq!(intern.get("world"), Some(1));
+    /// ```
+    fn from_iter<I: IntoIterator<Item = V>>(iter: I) -> Self {
+        let mut intern = Self::with_hasher(S::default());
+        intern.extend(iter);
+        intern
+    }
+}
+
+impl<Sym, S> From<Vec<String>> for Intern<'_, Sym, S>
+where
+    Sym: Symbol,
+    S: BuildHasher + Default,
+{
+    /// Build an intern table from owned strings, moving their buffers into
+    /// the table where possible.
+    /// Repeated strings collapse into a single id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let intern: Intern = vec![String::from("a"), String::from("a")].into();
+    /// assert_eq!(intern.len(), 1);
+    /// ```
+    fn from(strings: Vec<String>) -> Self {
+        let mut intern = Self::with_hasher(S::default());
+        intern.reserve(strings.len());
+        for s in strings {
+            intern.intern(s);
+        }
+        intern
+    }
+}
+
+impl<Sym, S> From<&[&str]> for Intern<'_, Sym, S>
+where
+    Sym: Symbol,
+    S: BuildHasher + Default,
+{
+    /// Build an intern table from borrowed strings.
+    /// Repeated strings collapse into a single id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let intern: Intern = Intern::from(&["a", "b", "a"][..]);
+    /// assert_eq!(intern.get("b"), Some(1));
+    /// ```
+    fn from(strings: &[&str]) -> Self {
+        let mut intern = Self::with_hasher(S::default());
+        intern.reserve(strings.len());
+        for s in strings {
+            intern.intern(*s);
+        }
+        intern
+    }
+}
+
+impl<V, Sym, S> Extend<V> for Intern<'_, Sym, S>
+where
+    V: Into<String> + AsRef<str>,
+    Sym: Symbol,
+    S: BuildHasher,
+{
+    /// Intern every string from an iterator.
+    /// Strings already in the table keep their existing ids.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// let hello = intern.intern("hello");
+    /// intern.extend(["world", "hello"]);
+    /// assert_eq!(intern.len(), 2);
+    /// assert_eq!(intern.get("hello"), Some(hello));
+    /// ```
+    fn extend<I: IntoIterator<Item = V>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve_for_input(iter.size_hint().0);
+        for s in iter {
+            self.intern(s);
+        }
+    }
+}
+
+impl<'a, Sym: Symbol, S> IntoIterator for Intern<'a, Sym, S> {
+    type Item = (Sym, Box<str>);
+    type IntoIter = IntoIter<'a, Sym>;
+
+    /// Consume the intern table, yielding every id and string in ascending id order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern("hello");
+    /// let mut iter = intern.into_iter();
+    /// assert_eq!(iter.next(), Some((0, "hello".into())));
+    /// assert_eq!(iter.next(), None);
+    /// ```
+    fn into_iter(self) -> IntoIter<'a, Sym> {
+        IntoIter::new(self.list)
+    }
+}
+
+impl<'a, Sym: Symbol, S: BuildHasher> IntoIterator for &'a Intern<'_, Sym, S> {
+    type Item = (Sym, &'a str);
+    type IntoIter = Iter<'a, Sym>;
+
+    /// Iterate over every id and string in ascending id order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::Intern;
+    ///
+    /// let mut intern = Intern::new();
+    /// intern.intern("hello");
+    /// for (id, s) in &intern {
+    ///     assert_eq!(intern.lookup(id), s);
+    /// }
+    /// ```
+    fn into_iter(self) -> Iter<'a, Sym> {
+        self.iter_with_ids()
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> fmt::Debug for Intern<'_, Sym, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Strings<'a, 'b, Sym, S>(&'a Intern<'b, Sym, S>);
+
+        impl<Sym: Symbol, S: BuildHasher> fmt::Debug for Strings<'_, '_, Sym, S> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_map().entries(self.0.iter_with_ids()).finish()
+            }
+        }
+
+        f.debug_struct("Intern")
+            .field("len", &self.len())
+            .field("strings", &Strings(self))
+            .finish()
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> Index<Sym> for Intern<'_, Sym, S> {
+    type Output = str;
+
+    /// Lookup the interned string by id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    #[track_caller]
+    fn index(&self, id: Sym) -> &str {
+        self.lookup(id)
+    }
+}
+
+// Every table is `Send` and `Sync` with the default id and hasher types.
+// This fails to compile if a change accidentally takes that away.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Intern<'static>>();
+    assert_send_sync::<ArenaIntern>();
+    assert_send_sync::<ByteIntern>();
+    assert_send_sync::<CaseInsensitiveIntern>();
+    assert_send_sync::<FrozenIntern<'static>>();
+    assert_send_sync::<RotatingIntern>();
+    assert_send_sync::<SmallIntern>();
+    #[cfg(feature = "unicode-normalization")]
+    assert_send_sync::<NormalizedIntern>();
+    #[cfg(feature = "std")]
+    assert_send_sync::<PathIntern>();
+    #[cfg(feature = "dashmap")]
+    assert_send_sync::<ShardedIntern>();
+    #[cfg(feature = "std")]
+    assert_send_sync::<SyncIntern>();
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interns_and_handles_lookup() {
+        let mut interner = Intern::new();
+        let id = interner.intern("hello");
+        assert_eq!(interner.lookup(id), "hello");
+        assert_eq!(interner.try_lookup(id), Some("hello"));
+    }
+
+    #[test]
+    fn get_does_not_intern() {
+        let mut interner = Intern::new();
+        assert_eq!(interner.get("hello"), None);
+        assert_eq!(interner.try_lookup(0), None);
+
+        let id = interner.intern("hello");
+        assert_eq!(interner.get("hello"), Some(id));
+        assert_eq!(interner.get("world"), None);
+        assert_eq!(interner.try_lookup(id + 1), None);
+        assert!(interner.contains("hello"));
+        assert!(!interner.contains("world"));
+        assert_eq!(interner.len(), 1);
+    }
+
+    #[test]
+    fn reallocate() {
+        let mut interner = Intern::with_capacity(1);
+        let id1 = interner.intern("hello");
+
+        // this should reallocate the internal list
+        let id2 = interner.intern("world");
+
+        assert_eq!(interner.lookup(id1), "hello");
+        assert_eq!(interner.try_lookup(id1), Some("hello"));
+        assert_eq!(interner.lookup(id2), "world");
+        assert_eq!(interner.try_lookup(id2), Some("world"));
+    }
+
+    #[test]
+    fn reserve_keeps_existing_ids() {
+        let mut interner = Intern::new();
+        let id = interner.intern("hello");
+        interner.reserve(100);
+        assert!(interner.capacity() >= 101);
+        assert_eq!(interner.lookup(id), "hello");
+        assert_eq!(interner.intern("hello"), id);
+    }
+
+    #[test]
+    fn iter_in_insertion_order() {
+        let mut interner = Intern::new();
+        interner.intern("b");
+        interner.intern("a");
+        interner.intern("b");
+        interner.intern("c");
+        assert_eq!(interner.iter().collect::<Vec<_>>(), ["b", "a", "c"]);
+        assert_eq!(
+            interner.iter_with_ids().collect::<Vec<_>>(),
+            [(0, "b"), (1, "a"), (2, "c")]
+        );
+    }
+
+    #[test]
+    fn index() {
+        let mut interner = Intern::new();
+        let id = interner.intern("hello");
+        assert_eq!(&interner[id], "hello");
+    }
+
+    #[test]
+    #[should_panic = "InternId 0 out of range (only 0 strings interned)"]
+    fn index_out_of_range() {
+        let interner = Intern::new();
+        let _ = &interner[0];
+    }
+
+    #[test]
+    fn debug() {
+        let mut interner = Intern::new();
+        interner.intern("hello");
+        interner.intern("world");
+        assert_eq!(
+            format!("{interner:?}"),
+            r#"Intern { len: 2, strings: {0: "hello", 1: "world"} }"#
+        );
+    }
+
+    #[test]
+    fn clone_outlives_original() {
+        let mut interner = Intern::new();
+        let hello = interner.intern("hello");
+        let world = interner.intern("world");
+
+        let mut cloned = interner.clone();
+        drop(interner);
+
+        assert_eq!(cloned.lookup(hello), "hello");
+        assert_eq!(cloned.lookup(world), "world");
+        assert_eq!(cloned.get("hello"), Some(hello));
+        assert_eq!(cloned.intern("world"), world);
+        assert_eq!(cloned.intern("again"), 2);
+    }
+
+    #[test]
+    fn eq_compares_ids_and_strings() {
+        assert_eq!(Intern::new(), Intern::with_capacity(10));
+
+        let mut a = Intern::new();
+        a.intern("hello");
+        a.intern("world");
+
+        let mut b = Intern::new();
+        b.intern("hello");
+        b.intern("world");
+        b.intern("hello");
+        assert_eq!(a, b);
+
+        let mut c = Intern::new();
+        c.intern("world");
+        c.intern("hello");
+        assert_ne!(a, c);
+        assert_ne!(a, Intern::new());
+    }
+
+    #[test]
+    fn try_intern() {
+        let mut interner = Intern::new();
+        let id = interner.try_intern("hello").unwrap();
+        assert_eq!(interner.try_intern("hello"), Ok(id));
+        assert_eq!(interner.lookup(id), "hello");
+        assert_eq!(
+            InternError::IdSpaceExhausted.to_string(),
+            "intern table has run out of ids"
+        );
+    }
+
+    #[test]
+    fn rejects_long_strings() {
+        let mut interner = Intern::new();
+        let long = interner.intern("long string");
+        interner.set_max_len(Some(4));
+        assert_eq!(interner.try_intern("short"), Err(InternError::TooLong));
+        assert_eq!(
+            interner.try_intern(String::from("short")),
+            Err(InternError::TooLong)
+        );
+        assert_eq!(interner.try_intern("long string"), Ok(long));
+        assert_eq!(interner.try_intern("four"), Ok(1));
+        assert_eq!(interner.len(), 2);
+        assert_eq!(
+            InternError::TooLong.to_string(),
+            "string is too long to be interned"
+        );
+    }
+
+    #[test]
+    fn custom_hasher() {
+        let mut interner =
+            Intern::<InternId, _>::with_hasher(std::collections::hash_map::RandomState::new());
+        let id = interner.intern("hello");
+        assert_eq!(interner.intern("hello"), id);
+        assert_eq!(interner.lookup(id), "hello");
+        assert_eq!(interner.clone(), interner);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "too slow under miri")]
+    fn narrow_ids() {
+        let mut interner = Intern::<u16>::default();
+        for i in 0..=u16::MAX as usize {
+            assert_eq!(interner.try_intern(i.to_string()), Ok(i as u16));
+        }
+        assert_eq!(
+            interner.try_intern("one too many"),
+            Err(InternError::IdSpaceExhausted)
+        );
+        assert_eq!(interner.try_intern("0"), Ok(0));
+        assert_eq!(interner.lookup(u16::MAX), u16::MAX.to_string());
+    }
+
+    #[test]
+    fn clear_restarts_ids() {
+        let mut interner = Intern::new();
+        interner.intern("hello");
+        interner.intern("world");
+        interner.clear();
+
+        assert!(interner.is_empty());
+        assert_eq!(interner.get("hello"), None);
+        assert_eq!(interner.try_lookup(0), None);
+        assert_eq!(interner.intern("foo"), 0);
+        assert_eq!(interner.lookup(0), "foo");
+    }
+
+    #[test]
+    fn collect_from_owned_and_borrowed() {
+        let tokens = vec!["a".to_string(), "b".to_string(), "a".to_string()];
+        let borrowed: Intern = tokens.iter().map(String::as_str).collect();
+        let owned: Intern = tokens.into_iter().collect();
+
+        assert_eq!(owned.len(), 2);
+        assert_eq!(owned.get("a"), Some(0));
+        assert_eq!(owned.get("b"), Some(1));
+        assert_eq!(owned, borrowed);
+    }
+
+    #[test]
+    fn extend_preserves_existing_ids() {
+        let mut interner: Intern = ["a", "b"].into_iter().collect();
+        interner.extend(vec!["c".to_string(), "a".to_string()]);
+        interner.extend(["d", "b"]);
+        assert_eq!(
+            interner.iter_with_ids().collect::<Vec<_>>(),
+            [(0, "a"), (1, "b"), (2, "c"), (3, "d")]
+        );
+    }
+
+    #[test]
+    fn shrink_to_fit_keeps_lookups_valid() {
+        let mut interner = Intern::with_capacity(64);
+        let ids = (0..10)
+            .map(|i| interner.intern(i.to_string()))
+            .collect::<Vec<_>>();
+        interner.shrink_to_fit();
+
+        assert!(interner.capacity() >= 10);
+        for (i, id) in ids.into_iter().enumerate() {
+            assert_eq!(interner.lookup(id), i.to_string());
+            assert_eq!(interner.get(&i.to_string()), Some(id));
+        }
+    }
+
+    #[test]
+    fn memory_usage() {
+        let empty = Intern::new().memory_usage();
+        assert_eq!(empty, MemoryUsage::default());
+
+        let mut interner = Intern::new();
+        interner.intern("hello");
+        interner.intern("world!");
+        interner.intern("hello");
+        let usage = interner.memory_usage();
+        assert_eq!(usage.strings_bytes, 11);
+        assert!(usage.list_overhead >= 2 * std::mem::size_of::<Cow<str>>());
+        assert!(usage.map_overhead > 0);
+    }
+
+    #[test]
+    fn lookups_survive_many_reallocations() {
+        let mut interner = Intern::new();
+        let ids = (0..200)
+            .map(|i| interner.intern(format!("string {i}")))
+            .collect::<Vec<_>>();
+        for (i, id) in ids.iter().enumerate() {
+            assert_eq!(interner.lookup(*id), format!("string {i}"));
+            assert_eq!(interner.intern(format!("string {i}")), *id);
+        }
+    }
+
+    #[test]
+    fn precomputed_hashes() {
+        let mut interner = Intern::new();
+        let words = ["hello", "world", "hello"];
+        let hashes = words.map(|w| interner.hash(w));
+
+        let ids = words.map(|w| interner.intern_with_hash(w, interner.hash(w)));
+        assert_eq!(ids, [0, 1, 0]);
+        for (w, hash) in words.iter().zip(hashes) {
+            assert_eq!(interner.get_with_hash(w, hash), interner.get(w));
+        }
+        assert_eq!(interner.get_with_hash("foo", interner.hash("foo")), None);
+    }
+
+    #[test]
+    fn merge_remaps_ids() {
+        let mut a: Intern = ["a", "b"].into_iter().collect();
+        let b: Intern = ["c", "b", "d"].into_iter().collect();
+
+        let remap = a.merge(&b);
+        assert_eq!(remap, [2, 1, 3]);
+        for (old, s) in b.iter_with_ids() {
+            assert_eq!(a.lookup(remap[old as usize]), s);
+        }
+        assert_eq!(a.len(), 4);
+        assert!(a.merge(&Intern::new()).is_empty());
+    }
+
+    #[test]
+    fn into_iter_owned_and_borrowed() {
+        let interner: Intern = ["a", "b", "a", "c"].into_iter().collect();
+
+        let borrowed = (&interner).into_iter().collect::<Vec<_>>();
+        assert_eq!(borrowed, [(0, "a"), (1, "b"), (2, "c")]);
+        assert_eq!(interner.iter_with_ids().len(), 3);
+        assert_eq!(interner.iter_with_ids().next_back(), Some((2, "c")));
+
+        let owned = interner.into_iter().collect::<Vec<_>>();
+        assert_eq!(owned, [(0, "a".into()), (1, "b".into()), (2, "c".into())]);
+    }
+
+    #[test]
+    fn intern_static_borrows() {
+        let mut interner = Intern::new();
+        let kw = interner.intern_static("fn");
+        let owned = interner.intern(String::from("let"));
+        assert_eq!(interner.intern("fn"), kw);
+        assert_eq!(interner.intern_static("let"), owned);
+        assert_eq!(interner.lookup(kw), "fn");
+        assert_eq!(interner.memory_usage().strings_bytes, 3);
+        assert_eq!(interner.into_strings(), [Box::from("fn"), Box::from("let")]);
+    }
+
+    #[test]
+    fn intern_cow_reuses_owned_buffer() {
+        let mut interner = Intern::new();
+        let owned = String::from("hello");
+        let ptr = owned.as_ptr();
+        let id = interner.intern_cow(Cow::Owned(owned));
+        assert_eq!(interner.lookup(id).as_ptr(), ptr);
+
+        let borrowed = String::from("world");
+        let world = interner.intern_cow(Cow::Borrowed(&borrowed));
+        assert_ne!(interner.lookup(world).as_ptr(), borrowed.as_ptr());
+        assert_eq!(interner.intern_cow(Cow::Borrowed("hello")), id);
+    }
+
+    #[test]
+    #[cfg(feature = "stats")]
+    fn counts_hits_and_misses() {
+        let mut interner = Intern::new();
+        assert_eq!(interner.stats(), InternStats::default());
+        assert_eq!(interner.stats().hit_rate(), 0.0);
+
+        interner.intern("hello");
+        interner.intern_static("hello");
+        interner.get_or_intern("world");
+        interner.get("hello");
+        let stats = interner.stats();
+        assert_eq!((stats.hits, stats.misses, stats.unique), (1, 2, 2));
+        assert_eq!(stats.calls(), 3);
+        assert_eq!(stats.bytes_saved, 5);
+
+        interner.clear();
+        interner.intern("world");
+        interner.intern("world");
+        assert_eq!(interner.stats().bytes_saved, 10);
+    }
+
+    #[test]
+    fn from_sorted_is_order_independent() {
+        let words = ["pear", "apple", "fig", "apple", "banana"];
+        let forward = Intern::from_sorted(words.map(String::from));
+        let backward = Intern::from_sorted(words.iter().rev().copied());
+        assert_eq!(forward, backward);
+        assert_eq!(forward.len(), 4);
+        assert_eq!(
+            forward.iter().collect::<Vec<_>>(),
+            ["apple", "banana", "fig", "pear"]
+        );
+    }
+
+    #[test]
+    fn drain_empties_the_table() {
+        let mut interner = Intern::new();
+        interner.intern("hello");
+        interner.intern_static("world");
+        interner.intern("foo");
+
+        let mut drain = interner.drain();
+        assert_eq!(drain.next(), Some((0, Box::from("hello"))));
+        drop(drain);
+        assert!(interner.is_empty());
+        assert_eq!(interner.get("world"), None);
+        assert_eq!(interner.intern("foo"), 0);
+    }
+
+    #[test]
+    fn intern_all_keeps_input_order() {
+        let mut interner = Intern::new();
+        let world = interner.intern("world");
+        let ids = interner.intern_all(vec![String::from("hello"), String::from("world")]);
+        assert_eq!(ids, [1, world]);
+        assert!(interner.intern_all(Vec::<&str>::new()).is_empty());
+    }
+
+    #[test]
+    fn intern_owned_keeps_buffer() {
+        let mut interner = Intern::new();
+        let hello = interner.intern("hello");
+        assert_eq!(interner.intern_owned(String::from("hello")), hello);
+
+        let mut world = String::with_capacity(32);
+        world.push_str("world");
+        let ptr = world.as_ptr();
+        let id = interner.intern_owned(world);
+        assert_eq!(interner.lookup(id).as_ptr(), ptr);
+        assert_eq!(interner.memory_usage().strings_bytes, 5 + 32);
+    }
+
+    #[test]
+    fn rollback_removes_later_strings() {
+        let mut interner = Intern::new();
+        let keep = interner.intern("keep");
+        let checkpoint = interner.checkpoint();
+        for s in ["a", "b", "c", "keep"] {
+            interner.intern(s);
+        }
+        interner.rollback(checkpoint);
+        assert_eq!(interner.len(), 1);
+        assert_eq!(interner.get("keep"), Some(keep));
+        assert_eq!(interner.get("b"), None);
+        assert_eq!(interner.try_lookup(1), None);
+        assert_eq!(interner.intern("c"), 1);
+
+        // a checkpoint past the end of the table is ignored.
+        let later = interner.checkpoint();
+        interner.clear();
+        interner.intern("x");
+        interner.rollback(later);
+        assert_eq!(interner.len(), 1);
+    }
+
+    #[test]
+    fn get_or_intern_with_builds_on_miss_only() {
+        let mut interner = Intern::new();
+        let mut calls = 0;
+        for _ in 0..3 {
+            let id = interner.get_or_intern_with("hello", || {
+                calls += 1;
+                String::from("hello")
+            });
+            assert_eq!(id, 0);
+        }
+        assert_eq!(calls, 1);
+        assert_eq!(interner.get("hello"), Some(0));
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn get_or_intern_with_checks_key() {
+        let mut interner = Intern::new();
+        interner.get_or_intern_with("hello", || String::from("world"));
+    }
+
+    #[test]
+    fn gc_compacts_live_ids() {
+        let mut interner = Intern::new();
+        let ids = interner.intern_all(["a", "b", "c", "d", "e"]);
+        let remap = interner.gc(&[ids[3], ids[1], ids[3]]);
+        assert_eq!(remap, [None, Some(0), None, Some(1), None]);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.iter().collect::<Vec<_>>(), ["b", "d"]);
+        assert_eq!(interner.get("d"), Some(1));
+        assert_eq!(interner.get("a"), None);
+        assert_eq!(interner.intern("a"), 2);
+
+        assert_eq!(interner.gc(&[]), [None, None, None]);
+        assert!(interner.is_empty());
+    }
+
+    #[test]
+    fn hash_follows_contents() {
+        let hash = |interner: &Intern| FxBuildHasher.hash_one(interner);
+        let a: Intern = ["a", "b"].into_iter().collect();
+        let mut b = Intern::with_capacity(16);
+        b.intern_static("a");
+        b.intern("b");
+        let c: Intern = ["b", "a"].into_iter().collect();
+        assert_eq!(hash(&a), hash(&b));
+        assert_ne!(hash(&a), hash(&c));
+
+        let set: std::collections::HashSet<_> = [a, b, c].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn retain_by_predicate() {
+        let mut interner = Intern::new();
+        interner.intern_all(["short", "a much longer string", "tiny", "x"]);
+        let mut seen = Vec::new();
+        let remap = interner.retain(|id, s| {
+            seen.push(id);
+            s.len() <= 5
+        });
+        assert_eq!(seen, [0, 1, 2, 3]);
+        assert_eq!(remap, [Some(0), None, Some(1), Some(2)]);
+        assert_eq!(interner.get("tiny"), Some(1));
+        assert!(!interner.contains("a much longer string"));
+        assert_eq!(interner.retain(|_, _| true), [Some(0), Some(1), Some(2)]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "too slow under miri")]
+    fn remaining_ids_counts_down_to_zero() {
+        let mut interner = Intern::<u16>::default();
+        for i in 0..=u16::MAX as usize {
+            assert_eq!(interner.remaining_ids(), (1 << 16) - i as u64);
+            interner.intern(i.to_string());
+        }
+        assert_eq!(interner.remaining_ids(), 0);
+        assert_eq!(Intern::<u64>::default().remaining_ids(), u64::MAX);
+        assert_eq!(Intern::new().remaining_ids(), 1 << 32);
+    }
+
+    #[test]
+    fn from_collections() {
+        let owned: Intern = vec![String::from("a"), String::from("b"), String::from("a")].into();
+        let borrowed: Intern = Intern::from(&["a", "b", "a"][..]);
+        assert_eq!(owned, borrowed);
+        assert_eq!(owned.len(), 2);
+        assert!(owned.capacity() >= 3);
+        assert_eq!(Intern::<u16>::from(Vec::new()).len(), 0);
+    }
+
+    #[test]
+    fn with_prefix_filters_in_id_order() {
+        let mut interner = Intern::new();
+        interner.intern_all(["foo", "bar", "foobar", "fo", "baz"]);
+        let found: Vec<_> = interner.with_prefix("foo").collect();
+        assert_eq!(found, [(0, "foo"), (2, "foobar")]);
+        assert_eq!(interner.with_prefix("").count(), 5);
+        assert_eq!(interner.with_prefix("qux").next(), None);
+    }
+
+    #[test]
+    fn lookup_unchecked_matches_lookup() {
+        let mut interner = Intern::new();
+        let ids = interner.intern_all(["a", "b", "c"]);
+        for id in ids {
+            // SAFETY: every id was returned by `intern_all` above.
+            assert_eq!(
+                unsafe { interner.lookup_unchecked(id) },
+                interner.lookup(id)
+            );
+        }
+    }
+
+    #[test]
+    fn extend_from_interner_matches_merge() {
+        let mut other = Intern::new();
+        other.intern_all(["b", "c", "a"]);
+        let mut merged = Intern::new();
+        merged.intern_all(["a", "d"]);
+        let mut extended = merged.clone();
+
+        merged.merge(&other);
+        extended.extend_from_interner(&other);
+        assert_eq!(extended, merged);
+        assert_eq!(extended.len(), 4);
+    }
+
+    #[test]
+    fn try_reserve_reports_overflow() {
+        let mut interner = Intern::new();
+        interner.intern("hello");
+        assert_eq!(interner.try_reserve(100), Ok(()));
+        assert!(interner.capacity() >= 101);
+        assert_eq!(
+            interner.try_reserve(usize::MAX / 2),
+            Err(InternError::AllocFailed)
+        );
+        assert_eq!(interner.intern("world"), 1);
+    }
+
+    #[test]
+    fn intern_or_passthrough_returns_long_strings() {
+        let mut interner = Intern::new();
+        let long = "a much longer string".to_string();
+        let ptr = long.as_ptr();
+        let passed = interner.intern_or_passthrough(long, 4).unwrap_err();
+        assert_eq!(passed.as_ptr(), ptr);
+        assert!(!interner.contains("a much longer string"));
+
+        let id = interner
+            .intern_or_passthrough("four".to_string(), 4)
+            .unwrap();
+        assert_eq!(interner.lookup(id), "four");
+        assert_eq!(
+            interner.intern_or_passthrough("four".to_string(), 4),
+            Ok(id)
+        );
+    }
+
+    #[test]
+    fn as_slice_is_indexed_by_id() {
+        let mut interner = Intern::new();
+        let ids = interner.intern_all(["b", "a", "b", "c"]);
+        let strings = interner.as_slice();
+        assert_eq!(strings.len(), interner.len());
+        for id in ids {
+            assert_eq!(strings[id as usize], interner.lookup(id));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn lookup_map_matches_get() {
+        let mut interner = Intern::new();
+        interner.intern_all(["a", "b", "c", "b"]);
+        let map = interner.to_lookup_map();
+        assert_eq!(map.len(), interner.len());
+        for (s, id) in &map {
+            assert_eq!(interner.get(s), Some(*id));
+        }
+    }
+
+    #[test]
+    fn intern_boxed_moves_allocation() {
+        let mut old = Intern::new();
+        old.intern_all(["a", "b"]);
+        let mut interner = Intern::new();
+        interner.intern("b");
+        let ids: Vec<_> = old
+            .into_strings()
+            .into_iter()
+            .map(|s| interner.intern_boxed(s))
+            .collect();
+        assert_eq!(ids, [1, 0]);
+        assert_eq!(interner.lookup(1), "a");
+    }
+
+    #[test]
+    fn pop_undoes_last_intern() {
+        let mut interner = Intern::new();
+        let a = interner.intern("a");
+        interner.intern_static("b");
+        assert_eq!(interner.pop(), Some((1, Box::from("b"))));
+        assert_eq!(interner.get("b"), None);
+        assert_eq!(interner.intern("c"), 1);
+        assert_eq!(interner.pop(), Some((1, Box::from("c"))));
+        assert_eq!(interner.pop(), Some((a, Box::from("a"))));
+        assert_eq!(interner.pop(), None);
+        assert!(interner.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn intern_lines_splits_like_lines() {
+        let input = "a\n\nb\r\na\nlast";
+        let mut interner = Intern::new();
+        let ids = interner.intern_lines(input.as_bytes()).unwrap();
+        let expected: Vec<_> = input.lines().map(|l| interner.intern(l)).collect();
+        assert_eq!(ids, expected);
+        assert_eq!(interner.len(), 4);
+
+        let ids = interner.intern_lines("y\r\nx\r".as_bytes()).unwrap();
+        assert_eq!(interner.lookup(ids[0]), "y");
+        assert_eq!(interner.lookup(ids[1]), "x\r");
+
+        let invalid: &[u8] = b"ok\n\xff\n";
+        assert!(interner.intern_lines(invalid).is_err());
+        assert!(interner.contains("ok"));
+    }
+
+    #[test]
+    fn intern_at_restores_ids_with_gaps() {
+        let mut interner = Intern::new();
+        interner.intern_at(3, "three").unwrap();
+        interner.intern_at(1, String::from("one")).unwrap();
+        assert_eq!(interner.len(), 4);
+        assert_eq!(interner.get("three"), Some(3));
+        assert_eq!(interner.try_lookup(0), Some(""));
+        assert_eq!(interner.get(""), None);
+        assert_eq!(interner.intern_at(3, "three"), Ok(()));
+        assert_eq!(interner.intern_at(2, "three"), Err(InternError::IdConflict));
+        assert_eq!(interner.intern_at(1, "uno"), Err(InternError::IdConflict));
+
+        // a real empty string is not a gap.
+        interner.intern_at(0, "").unwrap();
+        assert_eq!(interner.get(""), Some(0));
+        assert_eq!(interner.intern_at(0, "zero"), Err(InternError::IdConflict));
+        interner.intern_at(2, "two").unwrap();
+        assert_eq!(
+            interner.iter().collect::<Vec<_>>(),
+            ["", "one", "two", "three"]
+        );
+        assert_eq!(interner.intern("four"), 4);
+    }
+
+    #[test]
+    fn insert_reports_new_strings() {
+        let mut interner = Intern::new();
+        let inserted: Vec<_> = ["a", "b", "a", "c", "b"]
+            .into_iter()
+            .map(|s| interner.insert(s))
+            .collect();
+        assert_eq!(
+            inserted,
+            [(0, true), (1, true), (0, false), (2, true), (1, false)]
+        );
+    }
+
+    #[test]
+    fn length_histogram_caps_long_strings() {
+        let mut interner = Intern::new();
+        assert!(interner.length_histogram().is_empty());
+        for s in ["", "ab", "cd", "abcd"] {
+            interner.intern(s);
+        }
+        assert_eq!(interner.length_histogram(), [1, 0, 2, 0, 1]);
+
+        interner.intern("a".repeat(64));
+        interner.intern("a".repeat(1000));
+        let histogram = interner.length_histogram();
+        assert_eq!(histogram.len(), 65);
+        assert_eq!(histogram[64], 2);
+        assert_eq!(histogram.iter().sum::<usize>(), interner.len());
+    }
+
+    #[test]
+    #[should_panic = "InternId 5 out of range (only 2 strings interned)"]
+    fn lookup_names_invalid_id() {
+        let mut interner = Intern::new();
+        interner.intern("hello");
+        interner.intern("world");
+        interner.lookup(5);
+    }
+
+    #[test]
+    fn find_ids_yields_matches_in_id_order() {
+        let mut interner = Intern::new();
+        interner.intern_all(["alpha", "beta", "alphabet", "gamma"]);
+        let mut calls = 0;
+        let ids: Vec<_> = interner
+            .find_ids(|s| {
+                calls += 1;
+                s.contains("alpha")
+            })
+            .collect();
+        assert_eq!(ids, [0, 2]);
+        assert_eq!(calls, 4);
+        assert_eq!(interner.find_ids(|s| s.is_empty()).next(), None);
+    }
+
+    #[test]
+    fn into_narrow_keeps_ids() {
+        let mut interner: Intern = Intern::new();
+        let ids: Vec<_> = (0..1000).map(|i| interner.intern(i.to_string())).collect();
+        let narrow = interner.clone().into_narrow().unwrap();
+        for (i, id) in ids.into_iter().enumerate() {
+            assert_eq!(narrow.lookup(id as u16), i.to_string());
+            assert_eq!(narrow.get(&i.to_string()), Some(id as u16));
+        }
+        assert_eq!(narrow.len(), interner.len());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "too slow under miri")]
+    fn into_narrow_fails_when_too_long() {
+        let mut interner: Intern = Intern::new();
+        for i in 0..=u16::MAX as u32 {
+            interner.intern(i.to_string());
+        }
+        assert!(interner.clone().into_narrow().is_ok());
+
+        interner.intern("one too many");
+        let interner = interner.into_narrow().unwrap_err();
+        assert_eq!(interner.len(), u16::MAX as usize + 2);
+        assert_eq!(interner.get("one too many"), Some(u16::MAX as u32 + 1));
+    }
+
+    #[test]
+    fn intern_borrowed_does_not_copy() {
+        let arena = String::from("a b a c");
+        let mut interner = Intern::new();
+        let ids: Vec<_> = arena
+            .split(' ')
+            .map(|s| interner.intern_borrowed(s))
+            .collect();
+        assert_eq!(ids, [0, 1, 0, 2]);
+        assert!(interner
+            .as_slice()
+            .iter()
+            .all(|s| matches!(s, Cow::Borrowed(_))));
+        assert_eq!(interner.intern(String::from("b")), 1);
+        assert_eq!(interner.lookup(2).as_ptr(), arena[6..].as_ptr());
+    }
+
+    #[test]
+    fn try_lookup_many_matches_try_lookup() {
+        let mut interner = Intern::new();
+        interner.intern_all(["a", "b"]);
+        let ids = [1, 0, 2, u32::MAX];
+        let strings: Vec<_> = interner.try_lookup_many(&ids).collect();
+        assert_eq!(strings, [Some("b"), Some("a"), None, None]);
+        assert_eq!(interner.try_lookup_many(&[]).count(), 0);
+    }
+
+    #[test]
+    fn same_compares_ids() {
+        let mut interner = Intern::new();
+        let a = interner.intern("a");
+        let b = interner.intern("b");
+        let again = interner.intern("a");
+        assert!(interner.same(a, again));
+        assert!(!interner.same(a, b));
+        assert!(!interner.same(a, 9));
+
+        interner.intern_at(5, "").unwrap();
+        // the gaps before id 5 are empty too, but are not interned.
+        assert!(!interner.same(2, 5));
+        assert!(!interner.same(2, 3));
+        assert!(interner.debug_same(a, again));
+        assert!(!interner.debug_same(a, 5));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic = "ids 2 and 3 disagree with their strings"]
+    fn debug_same_checks_strings() {
+        let mut interner = Intern::new();
+        interner.intern_all(["a", "b"]);
+        interner.intern_at(4, "e").unwrap();
+        interner.debug_same(2, 3);
+    }
+
+    #[test]
+    fn byte_len_and_total_bytes() {
+        let mut interner = Intern::new();
+        assert_eq!(interner.total_bytes(), 0);
+        let empty = interner.intern("");
+        let long = interner.intern("x".repeat(300));
+        assert_eq!(interner.byte_len(empty), Some(0));
+        assert_eq!(interner.byte_len(long), Some(300));
+        assert_eq!(interner.byte_len(2), None);
+        assert_eq!(interner.total_bytes(), 300);
+        assert_eq!(
+            interner.total_bytes(),
+            interner
+                .ids()
+                .filter_map(|id| interner.byte_len(id))
+                .sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn with_capacity_and_hasher_presizes_both_tables() {
+        let mut interner: Intern<'_, u32, std::hash::RandomState> =
+            Intern::with_capacity_and_hasher(100, Default::default());
+        assert!(interner.list.capacity() >= 100);
+        assert!(interner.data.capacity() >= 100);
+        let id = interner.intern("hello");
+        assert_eq!(interner.get("hello"), Some(id));
+    }
+
+    #[test]
+    fn intern_char_matches_strings() {
+        let mut interner = Intern::new();
+        let ids: Vec<_> = "a+b+a€".chars().map(|c| interner.intern_char(c)).collect();
+        assert_eq!(ids, [0, 1, 2, 1, 0, 3]);
+        assert_eq!(interner.get("€"), Some(3));
+        assert_eq!(interner.byte_len(3), Some(3));
+    }
+
+    #[test]
+    fn intern_str_matches_intern() {
+        let mut interner = Intern::new();
+        let a = interner.intern_str("a");
+        assert_eq!(interner.intern("a"), a);
+        assert_eq!(interner.intern_str("a"), a);
+        assert_eq!(interner.intern_str("b"), 1);
+        assert!(matches!(interner.as_slice()[1], Cow::Owned(_)));
+    }
+
+    #[test]
+    fn validate_detects_inconsistencies() {
+        let mut interner = Intern::new();
+        interner.intern_all(["a", "b", "c", ""]);
+        interner.intern_at(7, "g").unwrap();
+        assert_eq!(interner.validate(), Ok(()));
+        interner.retain(|_, s| s != "b");
+        interner.pop();
+        let checkpoint = interner.checkpoint();
+        interner.intern("d");
+        interner.rollback(checkpoint);
+        assert_eq!(interner.validate(), Ok(()));
+        assert_eq!(interner.clone().into_narrow().unwrap().validate(), Ok(()));
+
+        let mut missing = interner.clone();
+        missing.list.push(Cow::Borrowed("x"));
+        let len = missing.len();
+        assert_eq!(
+            missing.validate(),
+            Err(ConsistencyError::MissingEntry(len - 1))
+        );
+
+        let mut unindexed = interner.clone();
+        let hash = unindexed.hasher.hash_one("a");
+        unindexed
+            .data
+            .find_entry(hash, |&id| id == 0)
+            .unwrap()
+            .remove();
+        assert_eq!(unindexed.validate(), Err(ConsistencyError::MissingEntry(0)));
+        assert_eq!(unindexed.intern_at(0, "z"), Err(InternError::IdConflict));
+
+        let mut duplicate = interner.clone();
+        duplicate.list.push(Cow::Borrowed("a"));
+        assert_eq!(
+            duplicate.validate(),
+            Err(ConsistencyError::DuplicateString(len - 1))
+        );
+
+        let mut dangling = interner.clone();
+        let hash = dangling.hasher.hash_one("a");
+        dangling.data.insert_unique(hash, 100, |_| hash);
+        assert_eq!(dangling.validate(), Err(ConsistencyError::DanglingId(100)));
+
+        let mut extra = interner.clone();
+        extra.data.insert_unique(hash, 0, |_| hash);
+        assert_eq!(extra.validate(), Err(ConsistencyError::ExtraEntries));
+    }
+
+    #[test]
+    fn intern_trimmed_stores_trimmed_form() {
+        let mut interner = Intern::new();
+        let foo = interner.intern_trimmed("  foo  ");
+        assert_eq!(interner.intern_trimmed("\nfoo"), foo);
+        assert_eq!(interner.intern("foo"), foo);
+        assert_eq!(interner.get(" foo"), None);
+        assert_eq!(interner.intern_trimmed(" \u{3000}"), interner.intern(""));
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    fn sorted_ids_sort_by_string() {
+        let mut interner = Intern::new();
+        assert!(interner.sorted_ids().is_empty());
+        interner.intern_all(["b", "B", "a", "ab", "", "é"]);
+        let sorted: Vec<_> = interner.sorted_iter().map(|(_, s)| s).collect();
+        assert_eq!(sorted, ["", "B", "a", "ab", "b", "é"]);
+        let ids = interner.sorted_ids();
+        assert_eq!(ids, [4, 1, 2, 3, 0, 5]);
+        assert!(ids
+            .windows(2)
+            .all(|w| interner.lookup(w[0]) < interner.lookup(w[1])));
+    }
+
+    /// Check that the table is consistent, and that interning any of its
+    /// strings again returns the id it has now.
+    fn assert_consistent<Sym: Symbol>(interner: &mut Intern<'_, Sym>) {
+        assert_eq!(interner.validate(), Ok(()));
+        let len = interner.len();
+        let strings: Vec<(Sym, String)> = interner
+            .iter_with_ids()
+            .filter(|&(id, _)| !interner.is_gap(id.to_usize()))
+            .map(|(id, s)| (id, s.to_string()))
+            .collect();
+        for (id, s) in strings {
+            assert_eq!(interner.get(&s), Some(id));
+            assert_eq!(interner.intern(s), id);
+        }
+        assert_eq!(interner.len(), len);
+    }
+
+    #[test]
+    fn intern_returns_current_ids_after_mutation() {
+        let mut interner = Intern::new();
+        interner.intern_all(["a", "b", "c", "d", "e"]);
+        assert_consistent(&mut interner);
+
+        let remap = interner.retain(|_, s| s != "b");
+        assert_eq!(interner.intern("c"), remap[2].unwrap());
+        assert_consistent(&mut interner);
+
+        let remap = interner.gc(&[0, 3]);
+        assert_eq!(interner.intern("e"), remap[3].unwrap());
+        assert_eq!(interner.intern("c"), 2);
+        assert_consistent(&mut interner);
+
+        let mut other = Intern::new();
+        other.intern_all(["z", "a", "y"]);
+        interner.merge(&other);
+        interner.extend_from_interner(&other);
+        assert_consistent(&mut interner);
+
+        let checkpoint = interner.checkpoint();
+        interner.intern_all(["tmp1", "tmp2"]);
+        interner.rollback(checkpoint);
+        interner.pop();
+        assert_eq!(interner.intern("tmp2"), interner.len() as u32 - 1);
+        assert_consistent(&mut interner);
+
+        interner.intern_at(20, "far").unwrap();
+        interner.intern_at(15, "").unwrap();
+        interner.intern_at(12, "gap").unwrap();
+        assert_consistent(&mut interner);
+        interner.retain(|_, s| s != "a");
+        assert_consistent(&mut interner);
+
+        interner.shrink_to_fit();
+        assert_consistent(&mut interner);
+        let mut narrow = interner.clone().into_narrow().unwrap();
+        assert_consistent(&mut narrow);
+
+        interner.drain().take(1).for_each(drop);
+        assert_eq!(interner.validate(), Ok(()));
+        assert_eq!(interner.intern("a"), 0);
+        interner.clear();
+        assert_eq!(interner.intern("b"), 0);
+        assert_consistent(&mut interner);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn write_to_and_read_from_round_trip() {
+        let mut interner = Intern::new();
+        interner.intern_all(["hello", "", "wörld", "hello"]);
+        interner.intern_static("static");
+        let mut bytes = Vec::new();
+        interner.write_to(&mut bytes).unwrap();
+
+        let restored: Intern = Intern::read_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!(restored, interner);
+        for (id, s) in interner.iter_with_ids() {
+            assert_eq!(restored.lookup(id), s);
+            assert_eq!(restored.get(s), Some(id));
+        }
+        assert_eq!(restored.validate(), Ok(()));
+
+        let empty: Intern = Intern::read_from(&mut &0u64.to_le_bytes()[..]).unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn read_from_rejects_bad_input() {
+        use std::io::ErrorKind;
+
+        let mut bytes = Vec::new();
+        Intern::<u32>::from(&["a", "b"][..])
+            .write_to(&mut bytes)
+            .unwrap();
+        let read = |bytes: &[u8]| Intern::<u32>::read_from(&mut &*bytes).unwrap_err().kind();
+        assert_eq!(read(&bytes[..bytes.len() - 1]), ErrorKind::UnexpectedEof);
+
+        let last = bytes.len() - 1;
+        bytes[last] = b'a';
+        assert_eq!(read(&bytes), ErrorKind::InvalidData);
+        bytes[last] = 0xff;
+        assert_eq!(read(&bytes), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn panic_while_storing_leaves_table_consistent() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        struct Panicky;
+        impl AsRef<str> for Panicky {
+            fn as_ref(&self) -> &str {
+                "panicky"
+            }
+        }
+        impl From<Panicky> for String {
+            fn from(_: Panicky) -> String {
+                panic!("conversion failed")
+            }
+        }
+
+        let mut interner = Intern::new();
+        interner.intern("hello");
+        let result = catch_unwind(AssertUnwindSafe(|| interner.intern(Panicky)));
+        assert!(result.is_err());
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            interner.get_or_intern_with("world", || panic!("make failed"))
+        }));
+        assert!(result.is_err());
+
+        assert_eq!(interner.validate(), Ok(()));
+        assert_eq!(interner.len(), 1);
+        assert_eq!(interner.intern("world"), 1);
+        assert_eq!(interner.intern("panicky"), 2);
+        assert_eq!(interner.validate(), Ok(()));
+    }
+
+    #[test]
+    fn intern_at_far_id_fails_without_allocating() {
+        let mut interner: Intern<usize> = Intern::default();
+        assert_eq!(
+            interner.intern_at(usize::MAX, "x"),
+            Err(InternError::IdSpaceExhausted)
+        );
+        assert_eq!(
+            interner.intern_at(usize::MAX / 2, "x"),
+            Err(InternError::AllocFailed)
+        );
+        assert!(interner.is_empty());
+        assert_eq!(interner.get("x"), None);
+        assert_eq!(interner.intern("x"), 0);
+        assert_eq!(interner.validate(), Ok(()));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn write_to_refuses_tables_with_gaps() {
+        let mut interner = Intern::new();
+        interner.intern_at(2, "two").unwrap();
+        interner.intern_at(3, "").unwrap();
+        let mut bytes = Vec::new();
+        let err = interner.write_to(&mut bytes).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(bytes.is_empty());
+
+        interner.intern_at(0, "zero").unwrap();
+        interner.intern_at(1, "one").unwrap();
+        interner.write_to(&mut bytes).unwrap();
+        let mut restored: Intern = Intern::read_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!(restored, interner);
+        assert_eq!(restored.get(""), Some(3));
+        assert_eq!(restored.intern_at(1, "x"), Err(InternError::IdConflict));
+        assert_eq!(restored.validate(), Ok(()));
+    }
+
+    #[test]
+    fn gaps_are_not_counted_as_strings() {
+        let mut interner = Intern::new();
+        interner.intern_at(3, "ab").unwrap();
+        interner.intern_at(1, "").unwrap();
+        assert_eq!(interner.len(), 4);
+        assert_eq!(interner.sorted_ids(), [1, 3]);
+        assert_eq!(interner.length_histogram(), [1, 0, 1]);
+        #[cfg(feature = "stats")]
+        assert_eq!(interner.stats().unique, 2);
     }
 }
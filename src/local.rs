@@ -0,0 +1,243 @@
+use core::cell::{Ref, RefCell};
+use core::fmt;
+use core::hash::BuildHasher;
+
+use alloc::string::String;
+use rustc_hash::FxBuildHasher;
+
+use crate::{Intern, InternError, InternId, Symbol};
+
+/// A single threaded string intern table which interns through `&self`.
+///
+/// `LocalIntern` wraps an [`Intern`] in a `RefCell`, so it can be shared
+/// by reference throughout a program, for example stored in an `Rc`, while
+/// any part of it keeps interning strings. It is not `Sync`; use
+/// [`SyncIntern`](crate::SyncIntern) to share a table between threads.
+///
+/// Like [`SyncIntern::lookup`](crate::SyncIntern::lookup), strings returned
+/// by [`LocalIntern::lookup`] borrow the table itself rather than the
+/// `RefCell`. This is sound for the same reason: every interned string lives
+/// in its own heap allocation which is never moved or freed while the table
+/// is shared.
+///
+/// # Examples
+///
+/// ```
+/// use intern_string::LocalIntern;
+///
+/// let intern = LocalIntern::new();
+/// let hello = intern.lookup(intern.intern("hello"));
+/// let world = intern.intern("world");
+/// assert_eq!(hello, "hello");
+/// assert_eq!(intern.get("world"), Some(world));
+/// ```
+pub struct LocalIntern<Sym = InternId, S = FxBuildHasher> {
+    inner: RefCell<Intern<'static, Sym, S>>,
+}
+
+impl LocalIntern {
+    /// Create a new single threaded intern table.
+    pub fn new() -> Self {
+        Self::from(Intern::new())
+    }
+
+    /// Create a new single threaded intern table with the given capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::from(Intern::with_capacity(capacity))
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> LocalIntern<Sym, S> {
+    /// Borrow the inner table.
+    ///
+    /// The inner table is only borrowed mutably while interning, so this
+    /// only panics if the input's `AsRef` or `Into` implementation calls back
+    /// into the table.
+    fn borrow(&self) -> Ref<'_, Intern<'static, Sym, S>> {
+        self.inner.borrow()
+    }
+
+    /// Intern a string.
+    /// Returns the interned id.
+    /// If the string is already interned, returns the existing id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`LocalIntern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::LocalIntern;
+    ///
+    /// let intern = LocalIntern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.lookup(id), "hello");
+    /// ```
+    #[inline]
+    pub fn intern<V: Into<String> + AsRef<str>>(&self, input: V) -> Sym {
+        match self.try_intern(input) {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Intern a string.
+    /// Returns the interned id, or an error if the string could not be interned.
+    ///
+    /// # Errors
+    ///
+    /// Fails for the same reasons as [`Intern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::LocalIntern;
+    ///
+    /// let intern = LocalIntern::new();
+    /// let id = intern.try_intern("hello").unwrap();
+    /// assert_eq!(intern.lookup(id), "hello");
+    /// ```
+    #[inline]
+    pub fn try_intern<V: Into<String> + AsRef<str>>(&self, input: V) -> Result<Sym, InternError> {
+        self.inner.borrow_mut().try_intern(input)
+    }
+
+    /// Get the id of an already interned string.
+    /// Returns `None` if the string has not been interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::LocalIntern;
+    ///
+    /// let intern = LocalIntern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.get("hello"), Some(id));
+    /// assert_eq!(intern.get("world"), None);
+    /// ```
+    #[inline]
+    pub fn get(&self, input: &str) -> Option<Sym> {
+        self.borrow().get(input)
+    }
+
+    /// Lookup the interned string by id.
+    ///
+    /// The returned string stays valid for as long as the table is borrowed,
+    /// even while more strings are interned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::LocalIntern;
+    ///
+    /// let intern = LocalIntern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.lookup(id), "hello");
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn lookup(&self, id: Sym) -> &str {
+        let s: *const str = self.borrow().lookup(id);
+        // SAFETY: the string is either `'static` or owned by its own heap
+        // allocation in the inner table. That allocation does not move when
+        // the table grows and is only freed through `&mut self` or by
+        // consuming `self`, neither of which can happen while the returned
+        // borrow of `self` is alive.
+        unsafe { &*s }
+    }
+
+    /// Lookup the interned string by id.
+    /// Returns `None` if the id is not valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::LocalIntern;
+    ///
+    /// let intern = LocalIntern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.try_lookup(id), Some("hello"));
+    /// ```
+    #[inline]
+    pub fn try_lookup(&self, id: Sym) -> Option<&str> {
+        let s: *const str = self.borrow().try_lookup(id)?;
+        // SAFETY: see `lookup`.
+        Some(unsafe { &*s })
+    }
+
+    /// Returns the number of unique strings in the intern table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.borrow().len()
+    }
+
+    /// Returns `true` if no strings have been interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.borrow().is_empty()
+    }
+
+    /// Get mutable access to the inner intern table.
+    /// No borrow checking at runtime is needed since this borrows the table
+    /// exclusively.
+    pub fn get_mut(&mut self) -> &mut Intern<'static, Sym, S> {
+        self.inner.get_mut()
+    }
+
+    /// Consume the single threaded table, returning the inner intern table.
+    pub fn into_inner(self) -> Intern<'static, Sym, S> {
+        self.inner.into_inner()
+    }
+}
+
+impl<Sym, S> From<Intern<'static, Sym, S>> for LocalIntern<Sym, S> {
+    fn from(intern: Intern<'static, Sym, S>) -> Self {
+        Self {
+            inner: RefCell::new(intern),
+        }
+    }
+}
+
+impl<Sym, S: Default> Default for LocalIntern<Sym, S> {
+    fn default() -> Self {
+        Self {
+            inner: RefCell::new(Intern::default()),
+        }
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> fmt::Debug for LocalIntern<Sym, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("LocalIntern").field(&*self.borrow()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::rc::Rc;
+    use alloc::string::ToString;
+
+    #[test]
+    fn lookup_survives_interning() {
+        let intern = Rc::new(LocalIntern::with_capacity(1));
+        let other = Rc::clone(&intern);
+        let hello = intern.lookup(intern.intern("hello"));
+        for i in 0..100 {
+            other.intern(i.to_string());
+        }
+        assert_eq!(hello, "hello");
+        assert_eq!(intern.get("99"), Some(100));
+        assert_eq!(intern.try_lookup(101), None);
+
+        drop(other);
+        let mut intern = Rc::into_inner(intern).unwrap();
+        intern.get_mut().clear();
+        assert!(intern.is_empty());
+    }
+}
@@ -0,0 +1,254 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{BuildHasher, Hasher};
+use core::ops::Index;
+
+use hashbrown::hash_table::Entry;
+use hashbrown::HashTable;
+use rustc_hash::FxBuildHasher;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::{invalid_id, InternError, InternId, Symbol};
+
+/// A string intern table which treats canonically equivalent strings as equal.
+///
+/// Strings are compared by their Unicode Normalization Form C, so `é` written
+/// as a single code point and `e` followed by a combining accent are interned
+/// under the same id. The table keeps the first string it saw exactly as it
+/// was given, and returns that from `lookup`.
+///
+/// Only canonical equivalence is used: compatibility variants, like `ﬁ` and
+/// `fi`, are still different strings. Normalizing makes interning slower than
+/// with [`Intern`](crate::Intern), but strings are normalized on the fly
+/// without allocating.
+///
+/// Requires the `unicode-normalization` feature.
+///
+/// # Examples
+///
+/// ```
+/// use intern_string::NormalizedIntern;
+///
+/// let mut intern = NormalizedIntern::new();
+/// let id = intern.intern("caf\u{e9}");
+/// assert_eq!(intern.intern("cafe\u{301}"), id);
+/// assert_eq!(intern.lookup(id), "caf\u{e9}");
+/// ```
+pub struct NormalizedIntern<Sym = InternId, S = FxBuildHasher> {
+    data: HashTable<Sym>,
+    list: Vec<Box<str>>,
+    hasher: S,
+}
+
+/// Hash a string as if it was normalized to NFC.
+fn hash_nfc<S: BuildHasher>(hasher: &S, input: &str) -> u64 {
+    let mut state = hasher.build_hasher();
+    for c in input.nfc() {
+        state.write_u32(c.into());
+    }
+    state.finish()
+}
+
+/// Check whether two strings are equal once normalized to NFC.
+#[inline]
+fn eq_nfc(a: &str, b: &str) -> bool {
+    a == b || a.nfc().eq(b.nfc())
+}
+
+impl NormalizedIntern {
+    /// Create a new normalizing intern table.
+    /// The table uses the default `FxBuildHasher`.
+    pub fn new() -> Self {
+        Self::with_hasher(FxBuildHasher)
+    }
+
+    /// Create a new normalizing intern table with the given capacity.
+    /// The table uses the default `FxBuildHasher`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: HashTable::with_capacity(capacity),
+            list: Vec::with_capacity(capacity),
+            hasher: FxBuildHasher,
+        }
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> NormalizedIntern<Sym, S> {
+    /// Create a new normalizing intern table which will use the given hasher.
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            data: HashTable::new(),
+            list: Vec::new(),
+            hasher,
+        }
+    }
+
+    /// Intern a string, ignoring differences in Unicode normalization.
+    /// Returns the interned id.
+    /// If an equivalent string is already interned, returns the existing id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every id is already in use. See [`NormalizedIntern::try_intern`].
+    #[inline]
+    pub fn intern<V: Into<String> + AsRef<str>>(&mut self, input: V) -> Sym {
+        match self.try_intern(input) {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Intern a string, ignoring differences in Unicode normalization.
+    /// Returns the interned id, or an error if the string could not be interned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::IdSpaceExhausted`] if every id is already in use.
+    pub fn try_intern<V: Into<String> + AsRef<str>>(
+        &mut self,
+        input: V,
+    ) -> Result<Sym, InternError> {
+        let hash = hash_nfc(&self.hasher, input.as_ref());
+        let Self { data, list, hasher } = self;
+        let entry = data.entry(
+            hash,
+            |id| eq_nfc(&list[id.to_usize()], input.as_ref()),
+            |id| hash_nfc(hasher, &list[id.to_usize()]),
+        );
+        match entry {
+            Entry::Occupied(entry) => Ok(*entry.get()),
+            Entry::Vacant(entry) => {
+                if list.len() > Sym::MAX {
+                    return Err(InternError::IdSpaceExhausted);
+                }
+                let id = Sym::from_usize(list.len());
+                list.push(input.into().into_boxed_str());
+                entry.insert(id);
+                Ok(id)
+            }
+        }
+    }
+
+    /// Get the id of an already interned string, ignoring differences in
+    /// Unicode normalization.
+    /// Returns `None` if no equivalent string has been interned.
+    #[inline]
+    pub fn get(&self, input: &str) -> Option<Sym> {
+        let hash = hash_nfc(&self.hasher, input);
+        self.data
+            .find(hash, |id| eq_nfc(&self.list[id.to_usize()], input))
+            .copied()
+    }
+
+    /// Check whether an equivalent string has already been interned.
+    #[inline]
+    pub fn contains(&self, input: &str) -> bool {
+        self.get(input).is_some()
+    }
+
+    /// Lookup the interned string by id.
+    /// Returns the string as it was first interned, which is not normalized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    #[track_caller]
+    pub fn lookup(&self, id: Sym) -> &str {
+        match self.list.get(id.to_usize()) {
+            Some(s) => s,
+            None => invalid_id(id.to_usize(), self.list.len()),
+        }
+    }
+
+    /// Lookup the interned string by id.
+    /// Returns `None` if the id is not valid.
+    #[inline]
+    pub fn try_lookup(&self, id: Sym) -> Option<&str> {
+        self.list.get(id.to_usize()).map(|s| &**s)
+    }
+
+    /// Returns the number of unique strings in the intern table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns `true` if no strings have been interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Iterate over all interned strings in insertion order.
+    /// The position of each string in the iteration is its id.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.list.iter().map(|s| &**s)
+    }
+}
+
+impl<Sym, S: Default> Default for NormalizedIntern<Sym, S> {
+    fn default() -> Self {
+        Self {
+            data: HashTable::new(),
+            list: Vec::new(),
+            hasher: S::default(),
+        }
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> fmt::Debug for NormalizedIntern<Sym, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NormalizedIntern")
+            .field("len", &self.len())
+            .field("strings", &self.list)
+            .finish()
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> Index<Sym> for NormalizedIntern<Sym, S> {
+    type Output = str;
+
+    /// Lookup the interned string by id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    #[track_caller]
+    fn index(&self, id: Sym) -> &str {
+        self.lookup(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merges_canonical_equivalents() {
+        let mut interner = NormalizedIntern::new();
+        // "Å" as one code point, as A with a ring, and as the angstrom sign.
+        let composed = interner.intern("\u{c5}ngstr\u{f6}m");
+        assert_eq!(interner.intern("A\u{30a}ngstro\u{308}m"), composed);
+        assert_eq!(interner.get("\u{212b}ngstr\u{f6}m"), Some(composed));
+        assert_eq!(interner.lookup(composed), "\u{c5}ngstr\u{f6}m");
+
+        // compatibility equivalents stay apart.
+        let ligature = interner.intern("\u{fb01}le");
+        assert_ne!(interner.intern("file"), ligature);
+        assert_eq!(interner.len(), 3);
+        assert_eq!(&interner[ligature], "\u{fb01}le");
+    }
+
+    #[test]
+    #[should_panic = "InternId 1 out of range (only 1 strings interned)"]
+    fn lookup_names_invalid_id() {
+        let mut interner = NormalizedIntern::new();
+        interner.intern("a");
+        interner.lookup(1);
+    }
+}
@@ -0,0 +1,222 @@
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::BuildHasher;
+use core::ops::Index;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use rustc_hash::FxBuildHasher;
+
+use crate::{ByteIntern, InternError, InternId, Symbol};
+
+/// A file path intern table.
+///
+/// Paths are interned by their raw OS bytes, so paths which are not valid
+/// UTF-8 are kept exactly as they are instead of being converted lossily.
+/// Two paths are only interned under the same id if they are byte for byte
+/// identical: `a/b` and `a//b` are different paths.
+///
+/// # Examples
+///
+/// ```
+/// use intern_string::PathIntern;
+/// use std::path::Path;
+///
+/// let mut intern = PathIntern::new();
+/// let id = intern.intern("src/lib.rs");
+/// assert_eq!(intern.intern(Path::new("src/lib.rs")), id);
+/// assert_eq!(intern.lookup(id), Path::new("src/lib.rs"));
+/// ```
+pub struct PathIntern<Sym = InternId, S = FxBuildHasher> {
+    /// Holds the `OsStr::as_encoded_bytes` of every interned path.
+    inner: ByteIntern<Sym, S>,
+}
+
+/// A path which hands out its encoded bytes, so a `PathBuf` can be moved into
+/// the byte table without copying.
+struct Encoded<P>(P);
+
+impl<P: AsRef<Path>> AsRef<[u8]> for Encoded<P> {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref().as_os_str().as_encoded_bytes()
+    }
+}
+
+impl<P: Into<PathBuf>> From<Encoded<P>> for Vec<u8> {
+    fn from(path: Encoded<P>) -> Self {
+        path.0.into().into_os_string().into_encoded_bytes()
+    }
+}
+
+impl PathIntern {
+    /// Create a new path intern table.
+    /// The table uses the default `FxBuildHasher`.
+    pub fn new() -> Self {
+        Self {
+            inner: ByteIntern::new(),
+        }
+    }
+
+    /// Create a new path intern table with the given capacity.
+    /// The table uses the default `FxBuildHasher`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            inner: ByteIntern::with_capacity(capacity),
+        }
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> PathIntern<Sym, S> {
+    /// Create a new path intern table which will use the given hasher.
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            inner: ByteIntern::with_hasher(hasher),
+        }
+    }
+
+    /// Intern a path.
+    /// Returns the interned id.
+    /// If the path is already interned, returns the existing id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every id is already in use. See [`PathIntern::try_intern`].
+    #[inline]
+    pub fn intern<P: Into<PathBuf> + AsRef<Path>>(&mut self, path: P) -> Sym {
+        self.inner.intern(Encoded(path))
+    }
+
+    /// Intern a path.
+    /// Returns the interned id, or an error if the path could not be interned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::IdSpaceExhausted`] if every id is already in use.
+    #[inline]
+    pub fn try_intern<P: Into<PathBuf> + AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<Sym, InternError> {
+        self.inner.try_intern(Encoded(path))
+    }
+
+    /// Get the id of an already interned path.
+    /// Returns `None` if the path has not been interned.
+    #[inline]
+    pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<Sym> {
+        self.inner.get(Encoded(path).as_ref())
+    }
+
+    /// Check whether a path has already been interned.
+    #[inline]
+    pub fn contains<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.get(path).is_some()
+    }
+
+    /// Lookup the interned path by id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    pub fn lookup(&self, id: Sym) -> &Path {
+        decode(self.inner.lookup(id))
+    }
+
+    /// Lookup the interned path by id.
+    /// Returns `None` if the id is not valid.
+    #[inline]
+    pub fn try_lookup(&self, id: Sym) -> Option<&Path> {
+        self.inner.try_lookup(id).map(decode)
+    }
+
+    /// Returns the number of unique paths in the intern table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if no paths have been interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Iterate over all interned paths in insertion order.
+    /// The position of each path in the iteration is its id.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Path> {
+        self.inner.iter().map(decode)
+    }
+}
+
+/// Turn bytes stored in the table back into a path.
+#[inline]
+fn decode(bytes: &[u8]) -> &Path {
+    // SAFETY: every byte string in the table was produced by
+    // `OsStr::as_encoded_bytes` or `OsString::into_encoded_bytes`, in this
+    // process, and is never modified afterwards.
+    Path::new(unsafe { OsStr::from_encoded_bytes_unchecked(bytes) })
+}
+
+impl<Sym, S: Default> Default for PathIntern<Sym, S> {
+    fn default() -> Self {
+        Self {
+            inner: ByteIntern::default(),
+        }
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> fmt::Debug for PathIntern<Sym, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PathIntern")
+            .field("len", &self.len())
+            .field("paths", &self.iter().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> Index<Sym> for PathIntern<Sym, S> {
+    type Output = Path;
+
+    /// Lookup the interned path by id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    fn index(&self, id: Sym) -> &Path {
+        self.lookup(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interns_paths() {
+        let mut interner = PathIntern::new();
+        let lib = interner.intern("src/lib.rs");
+        let main = interner.intern(PathBuf::from("src/main.rs"));
+        assert_eq!(interner.intern(Path::new("src/lib.rs")), lib);
+        assert_ne!(interner.intern("src//lib.rs"), lib);
+        assert_eq!(&interner[main], Path::new("src/main.rs"));
+        assert_eq!(interner.get("src/main.rs"), Some(main));
+        assert_eq!(interner.try_lookup(3), None);
+        assert_eq!(interner.len(), 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_invalid_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut interner = PathIntern::new();
+        let raw = Path::new(OsStr::from_bytes(b"caf\xe9.txt"));
+        let lossy = PathBuf::from(raw.to_string_lossy().into_owned());
+        let id = interner.intern(raw);
+        assert_ne!(interner.intern(&lossy), id);
+        assert_eq!(interner.lookup(id).as_os_str().as_bytes(), b"caf\xe9.txt");
+    }
+}
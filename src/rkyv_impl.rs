@@ -0,0 +1,245 @@
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::BuildHasher;
+use core::ops::Index;
+
+use rkyv::rancor::{Fallible, Source};
+use rkyv::ser::{Allocator, Writer};
+use rkyv::string::ArchivedString;
+use rkyv::vec::{ArchivedVec, VecResolver};
+use rkyv::with::{ArchiveWith, AsOwned, Map, SerializeWith};
+use rkyv::{Archive, Deserialize, Place, Portable, Serialize};
+
+use crate::{invalid_id, Intern, Symbol};
+
+/// The archived form of an [`Intern`], created with the `rkyv` feature.
+///
+/// Only the interned strings are archived, in id order. An `ArchivedIntern`
+/// can resolve ids to strings directly from the archived bytes, for example
+/// from a memory mapped file, without rebuilding anything. Looking up the id
+/// of a string needs the hash table, so deserialize the archive back into an
+/// [`Intern`] for that.
+///
+/// # Examples
+///
+/// ```
+/// use intern_string::{ArchivedIntern, Intern};
+///
+/// let mut intern = Intern::new();
+/// let id = intern.intern("hello");
+///
+/// let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&intern).unwrap();
+/// let archived = rkyv::access::<ArchivedIntern, rkyv::rancor::Error>(&bytes).unwrap();
+/// assert_eq!(archived.lookup(id), "hello");
+///
+/// let intern: Intern = rkyv::deserialize::<_, rkyv::rancor::Error>(archived).unwrap();
+/// assert_eq!(intern.get("hello"), Some(id));
+/// ```
+#[derive(Portable, rkyv::bytecheck::CheckBytes)]
+#[bytecheck(crate = rkyv::bytecheck)]
+#[rkyv(crate = rkyv)]
+#[repr(transparent)]
+pub struct ArchivedIntern {
+    list: ArchivedVec<ArchivedString>,
+}
+
+impl ArchivedIntern {
+    /// Lookup the archived string by id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    #[track_caller]
+    pub fn lookup<Sym: Symbol>(&self, id: Sym) -> &str {
+        match self.list.get(id.to_usize()) {
+            Some(s) => s,
+            None => invalid_id(id.to_usize(), self.list.len()),
+        }
+    }
+
+    /// Lookup the archived string by id.
+    /// Returns `None` if the id is not valid.
+    #[inline]
+    pub fn try_lookup<Sym: Symbol>(&self, id: Sym) -> Option<&str> {
+        self.list.get(id.to_usize()).map(ArchivedString::as_str)
+    }
+
+    /// Returns the number of archived strings.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns `true` if no strings were archived.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Iterate over all archived strings in id order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.list.iter().map(ArchivedString::as_str)
+    }
+}
+
+impl fmt::Debug for ArchivedIntern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchivedIntern")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Sym: Symbol> Index<Sym> for ArchivedIntern {
+    type Output = str;
+
+    /// Lookup the archived string by id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    #[track_caller]
+    fn index(&self, id: Sym) -> &str {
+        self.lookup(id)
+    }
+}
+
+/// The strings are archived as if they were owned, so borrowed strings are
+/// archived just the same.
+type Strings = Map<AsOwned>;
+
+impl<Sym, H> Archive for Intern<'_, Sym, H> {
+    type Archived = ArchivedIntern;
+    type Resolver = VecResolver;
+
+    fn resolve(&self, resolver: VecResolver, out: Place<ArchivedIntern>) {
+        rkyv::munge::munge!(let ArchivedIntern { list } = out);
+        <Strings as ArchiveWith<Vec<Cow<'_, str>>>>::resolve_with(&self.list, resolver, list);
+    }
+}
+
+/// Returned when a table with gaps is archived, since the archive could not
+/// tell the gaps from an interned empty string.
+#[derive(Debug)]
+struct HasGaps;
+
+impl fmt::Display for HasGaps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("cannot archive an intern table with gaps")
+    }
+}
+
+impl core::error::Error for HasGaps {}
+
+/// Fails if the table has gaps, see [`Intern::has_gaps`].
+impl<Sym, H, S> Serialize<S> for Intern<'_, Sym, H>
+where
+    Sym: Symbol,
+    H: BuildHasher,
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<VecResolver, S::Error> {
+        if self.has_gaps() {
+            return Err(S::Error::new(HasGaps));
+        }
+        <Strings as SerializeWith<Vec<Cow<'_, str>>, S>>::serialize_with(&self.list, serializer)
+    }
+}
+
+/// Returned when an archive holds the same string twice, which would shift
+/// the ids of every string after it.
+#[derive(Debug)]
+struct DuplicateString;
+
+impl fmt::Display for DuplicateString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("duplicate string in intern table")
+    }
+}
+
+impl core::error::Error for DuplicateString {}
+
+impl<'a, Sym, H, D> Deserialize<Intern<'a, Sym, H>, D> for ArchivedIntern
+where
+    Sym: Symbol,
+    H: BuildHasher + Default,
+    D: Fallible + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize(&self, _: &mut D) -> Result<Intern<'a, Sym, H>, D::Error> {
+        let mut intern = Intern::<Sym, H>::default();
+        intern.reserve(self.len());
+        for (index, s) in self.iter().enumerate() {
+            let id = intern.try_intern(s).map_err(D::Error::new)?;
+            if id.to_usize() != index {
+                return Err(D::Error::new(DuplicateString));
+            }
+        }
+        Ok(intern)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InternError;
+    use rkyv::rancor::Error;
+
+    #[test]
+    #[cfg_attr(miri, ignore = "rkyv validation needs tree borrows")]
+    fn rkyv_round_trip() {
+        let mut interner = Intern::new();
+        let hello = interner.intern("hello");
+        let world = interner.intern_static("world");
+
+        let bytes = rkyv::to_bytes::<Error>(&interner).unwrap();
+        let archived = rkyv::access::<ArchivedIntern, Error>(&bytes).unwrap();
+        assert_eq!(archived.len(), 2);
+        assert_eq!(&archived[world], "world");
+        assert_eq!(archived.try_lookup(2u32), None);
+        assert_eq!(archived.iter().collect::<Vec<_>>(), ["hello", "world"]);
+
+        let restored: Intern = rkyv::deserialize::<_, Error>(archived).unwrap();
+        assert_eq!(restored, interner);
+        assert_eq!(restored.get("hello"), Some(hello));
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "rkyv validation needs tree borrows")]
+    fn rkyv_rejects_duplicates() {
+        let strings = vec![String::from("a"), String::from("a")];
+        let bytes = rkyv::to_bytes::<Error>(&strings).unwrap();
+        let archived = rkyv::access::<ArchivedIntern, Error>(&bytes).unwrap();
+        let restored: Result<Intern, _> = rkyv::deserialize::<_, Error>(archived);
+        assert!(restored.is_err());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "rkyv validation needs tree borrows")]
+    fn rkyv_refuses_tables_with_gaps() {
+        let mut interner = Intern::new();
+        interner.intern_at(2, "two").unwrap();
+        interner.intern_at(3, "").unwrap();
+        assert!(rkyv::to_bytes::<Error>(&interner).is_err());
+        // gaps archived as empty strings are read back as duplicates.
+        let strings = vec![String::new(), String::new(), String::from("two")];
+        let bytes = rkyv::to_bytes::<Error>(&strings).unwrap();
+        let archived = rkyv::access::<ArchivedIntern, Error>(&bytes).unwrap();
+        assert!(rkyv::deserialize::<Intern, Error>(archived).is_err());
+
+        interner.intern_at(0, "zero").unwrap();
+        interner.intern_at(1, "one").unwrap();
+        let bytes = rkyv::to_bytes::<Error>(&interner).unwrap();
+        let archived = rkyv::access::<ArchivedIntern, Error>(&bytes).unwrap();
+        let mut restored: Intern = rkyv::deserialize::<_, Error>(archived).unwrap();
+        assert_eq!(restored, interner);
+        assert_eq!(restored.get(""), Some(3));
+        assert_eq!(restored.intern_at(1, "x"), Err(InternError::IdConflict));
+        assert_eq!(restored.validate(), Ok(()));
+    }
+}
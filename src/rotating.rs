@@ -0,0 +1,270 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::BuildHasher;
+use core::ops::Index;
+
+use rustc_hash::FxBuildHasher;
+
+use crate::Intern;
+
+/// The number of ids in a full generation.
+const GENERATION_LEN: u64 = u32::MAX as u64 + 1;
+
+/// A string intern table which never runs out of ids.
+///
+/// Strings are interned into a table of `u32` ids. Once the current table is
+/// full, a new one is started, and the strings of the next generation go
+/// there. The returned `u64` ids hold the generation in their upper 32 bits
+/// and the id within the generation in their lower 32 bits. Every string is
+/// still only interned once, across all generations.
+///
+/// # Examples
+///
+/// ```
+/// use intern_string::RotatingIntern;
+///
+/// let mut intern = RotatingIntern::with_generation_len(2);
+/// let a = intern.intern("a");
+/// intern.intern("b");
+/// let c = intern.intern("c");
+/// assert_eq!(c, 1 << 32);
+/// assert_eq!(intern.intern("a"), a);
+/// assert_eq!(intern.lookup(c), "c");
+/// assert_eq!(intern.generations(), 2);
+/// ```
+pub struct RotatingIntern<S = FxBuildHasher> {
+    generations: Vec<Intern<'static, u32, S>>,
+    generation_len: u64,
+}
+
+impl RotatingIntern {
+    /// Create a new rotating intern table.
+    /// The table uses the default `FxBuildHasher`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new rotating intern table which starts a new generation after
+    /// every `len` strings, instead of once `u32` runs out of ids.
+    /// The table uses the default `FxBuildHasher`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is zero or greater than the number of `u32` ids.
+    pub fn with_generation_len(len: usize) -> Self {
+        assert!(
+            (1..=GENERATION_LEN).contains(&(len as u64)),
+            "generation length must be between 1 and 2^32"
+        );
+        Self {
+            generations: vec![Intern::default()],
+            generation_len: len as u64,
+        }
+    }
+}
+
+impl<S: BuildHasher + Default> RotatingIntern<S> {
+    /// Split an id into its generation and the id within that generation.
+    #[inline]
+    fn split(id: u64) -> (usize, u32) {
+        ((id >> 32) as usize, id as u32)
+    }
+
+    /// Intern a string.
+    /// Returns the interned id.
+    /// If the string is already interned, in any generation, returns the
+    /// existing id.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::RotatingIntern;
+    ///
+    /// let mut intern = RotatingIntern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.intern(String::from("hello")), id);
+    /// ```
+    pub fn intern<V: Into<String> + AsRef<str>>(&mut self, input: V) -> u64 {
+        if let Some(id) = self.get(input.as_ref()) {
+            return id;
+        }
+        if self.current().len() as u64 >= self.generation_len {
+            self.generations.push(Intern::default());
+        }
+        let generation = self.generations.len() - 1;
+        let id = self.generations[generation].intern(input);
+        ((generation as u64) << 32) | u64::from(id)
+    }
+
+    /// The generation which new strings are interned into.
+    #[inline]
+    fn current(&self) -> &Intern<'static, u32, S> {
+        &self.generations[self.generations.len() - 1]
+    }
+
+    /// Get the id of an already interned string.
+    /// Returns `None` if the string has not been interned.
+    ///
+    /// Every generation is searched, newest first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::RotatingIntern;
+    ///
+    /// let mut intern = RotatingIntern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.get("hello"), Some(id));
+    /// assert_eq!(intern.get("world"), None);
+    /// ```
+    pub fn get(&self, input: &str) -> Option<u64> {
+        self.generations
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(generation, table)| {
+                let id = table.get(input)?;
+                Some(((generation as u64) << 32) | u64::from(id))
+            })
+    }
+
+    /// Lookup the interned string by id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid. The message tells an id from a
+    /// generation which does not exist apart from an id which is out of range
+    /// within its generation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::RotatingIntern;
+    ///
+    /// let mut intern = RotatingIntern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.lookup(id), "hello");
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn lookup(&self, id: u64) -> &str {
+        let (generation, index) = Self::split(id);
+        match self.generations.get(generation) {
+            Some(table) => table.lookup(index),
+            None => panic!(
+                "InternId {id} has a stale generation {generation} (only {} generations)",
+                self.generations.len()
+            ),
+        }
+    }
+
+    /// Lookup the interned string by id.
+    /// Returns `None` if the id is not valid.
+    #[inline]
+    pub fn try_lookup(&self, id: u64) -> Option<&str> {
+        let (generation, id) = Self::split(id);
+        self.generations.get(generation)?.try_lookup(id)
+    }
+
+    /// Returns the number of unique strings in all generations.
+    pub fn len(&self) -> usize {
+        self.generations.iter().map(Intern::len).sum()
+    }
+
+    /// Returns `true` if no strings have been interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.current().is_empty() && self.generations.len() == 1
+    }
+
+    /// Returns the number of generations, including the current one.
+    #[inline]
+    pub fn generations(&self) -> usize {
+        self.generations.len()
+    }
+}
+
+impl<S: Default> Default for RotatingIntern<S> {
+    fn default() -> Self {
+        Self {
+            generations: vec![Intern::default()],
+            generation_len: GENERATION_LEN,
+        }
+    }
+}
+
+impl<S: BuildHasher + Default> fmt::Debug for RotatingIntern<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RotatingIntern")
+            .field("len", &self.len())
+            .field("generations", &self.generations())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: BuildHasher + Default> Index<u64> for RotatingIntern<S> {
+    type Output = str;
+
+    /// Lookup the interned string by id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    #[track_caller]
+    fn index(&self, id: u64) -> &str {
+        self.lookup(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn rotates_when_generation_is_full() {
+        let mut interner = RotatingIntern::with_generation_len(3);
+        let ids: Vec<u64> = (0..7).map(|i| interner.intern(i.to_string())).collect();
+        assert_eq!(
+            ids,
+            [0, 1, 2, 1 << 32, (1 << 32) + 1, (1 << 32) + 2, 2 << 32]
+        );
+        assert_eq!(interner.generations(), 3);
+        assert_eq!(interner.len(), 7);
+
+        for (i, &id) in ids.iter().enumerate() {
+            assert_eq!(&interner[id], i.to_string());
+            assert_eq!(interner.intern(i.to_string()), id);
+        }
+        assert_eq!(interner.len(), 7);
+        assert_eq!(interner.try_lookup(3), None);
+        assert_eq!(interner.try_lookup(3 << 32), None);
+    }
+
+    #[test]
+    fn starts_empty() {
+        let interner = RotatingIntern::new();
+        assert!(interner.is_empty());
+        assert_eq!(interner.generations(), 1);
+        assert_eq!(interner.get(""), None);
+    }
+
+    #[test]
+    #[should_panic = "InternId 5 out of range (only 1 strings interned)"]
+    fn lookup_names_invalid_id() {
+        let mut interner = RotatingIntern::new();
+        interner.intern("a");
+        interner.lookup(5);
+    }
+
+    #[test]
+    #[should_panic = "InternId 4294967296 has a stale generation 1 (only 1 generations)"]
+    fn lookup_names_stale_generation() {
+        let mut interner = RotatingIntern::new();
+        interner.intern("a");
+        interner.lookup(1 << 32);
+    }
+}
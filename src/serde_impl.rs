@@ -0,0 +1,100 @@
+use alloc::string::String;
+use core::fmt;
+use core::hash::BuildHasher;
+use core::marker::PhantomData;
+
+use serde::de::{Deserialize, Deserializer, Error, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, Serializer};
+
+use crate::{Intern, Symbol};
+
+/// Serializes the intern table as a sequence of strings in id order.
+/// Fails if the table has gaps, see [`Intern::has_gaps`].
+impl<Sym: Symbol, H: BuildHasher> Serialize for Intern<'_, Sym, H> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.has_gaps() {
+            return Err(ser::Error::custom(
+                "cannot serialize an intern table with gaps",
+            ));
+        }
+        serializer.collect_seq(self.iter())
+    }
+}
+
+/// Deserializes a sequence of unique strings, assigning ids by position.
+impl<'de, Sym: Symbol, H: BuildHasher + Default> Deserialize<'de> for Intern<'_, Sym, H> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct InternVisitor<Sym, H>(PhantomData<(Sym, H)>);
+
+        impl<'de, Sym: Symbol, H: BuildHasher + Default> Visitor<'de> for InternVisitor<Sym, H> {
+            type Value = Intern<'static, Sym, H>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a sequence of unique strings")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut intern = Intern::<Sym, H>::with_hasher(H::default());
+                intern.reserve(seq.size_hint().unwrap_or(0));
+                while let Some(s) = seq.next_element::<String>()? {
+                    let expected = intern.len();
+                    if intern.try_intern(s).map_err(A::Error::custom)?.to_usize() != expected {
+                        return Err(A::Error::custom("duplicate string in intern table"));
+                    }
+                }
+                Ok(intern)
+            }
+        }
+
+        deserializer.deserialize_seq(InternVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InternError;
+
+    #[test]
+    fn round_trip_preserves_ids() {
+        let mut intern = Intern::new();
+        for s in ["hello", "world", "foo", "hello", "bar"] {
+            intern.intern(s);
+        }
+
+        let json = serde_json::to_string(&intern).unwrap();
+        assert_eq!(json, r#"["hello","world","foo","bar"]"#);
+
+        let restored: Intern = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, intern);
+        for (id, s) in intern.iter_with_ids() {
+            assert_eq!(restored.lookup(id), s);
+            assert_eq!(restored.get(s), Some(id));
+        }
+    }
+
+    #[test]
+    fn rejects_duplicates() {
+        assert!(serde_json::from_str::<Intern>(r#"["a","b","a"]"#).is_err());
+    }
+
+    #[test]
+    fn refuses_tables_with_gaps() {
+        let mut intern = Intern::new();
+        intern.intern_at(2, "two").unwrap();
+        intern.intern_at(3, "").unwrap();
+        assert!(serde_json::to_string(&intern).is_err());
+        // gaps written as empty strings are read back as duplicates.
+        assert!(serde_json::from_str::<Intern>(r#"["","","two"]"#).is_err());
+
+        intern.intern_at(0, "zero").unwrap();
+        intern.intern_at(1, "one").unwrap();
+        let json = serde_json::to_string(&intern).unwrap();
+        let mut restored: Intern = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, intern);
+        assert_eq!(restored.get(""), Some(3));
+        assert_eq!(restored.get("two"), Some(2));
+        assert_eq!(restored.intern_at(1, "x"), Err(InternError::IdConflict));
+        assert_eq!(restored.validate(), Ok(()));
+    }
+}
@@ -0,0 +1,421 @@
+use std::fmt;
+use std::hash::BuildHasher;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use rustc_hash::FxBuildHasher;
+
+use crate::{InternError, InternId, Symbol};
+
+/// A thread-safe string intern table for many concurrent writers.
+///
+/// Unlike [`SyncIntern`](crate::SyncIntern), which takes a single exclusive
+/// lock for every new string, `ShardedIntern` is split into shards. The ids
+/// are kept in a `DashMap`, and each shard appends its strings to a list of
+/// its own, so threads interning different strings rarely wait for each
+/// other. Lookups by id take no lock at all: a shard never moves a string
+/// once it has been added, so readers never wait for writers.
+/// Requires the `dashmap` feature.
+///
+/// Every id is unique across the table, but tells which shard its string is
+/// in, so ids are not consecutive. Each string is shared between the map and
+/// its shard by an `Arc`, so it is still only allocated once.
+///
+/// # Examples
+///
+/// ```
+/// use intern_string::ShardedIntern;
+///
+/// let intern = ShardedIntern::new();
+/// let ids = std::thread::scope(|s| {
+///     let a = s.spawn(|| intern.intern("hello"));
+///     let b = s.spawn(|| intern.intern("hello"));
+///     [a.join().unwrap(), b.join().unwrap()]
+/// });
+/// assert_eq!(ids[0], ids[1]);
+/// assert_eq!(intern.lookup(ids[0]), "hello");
+/// assert_eq!(intern.len(), 1);
+/// ```
+pub struct ShardedIntern<Sym = InternId, S = FxBuildHasher> {
+    map: DashMap<Arc<str>, Sym, S>,
+    shards: Box<[Shard]>,
+    hasher: S,
+}
+
+/// The number of buckets of a shard, enough for every index.
+const BUCKETS: usize = usize::BITS as usize;
+
+/// A bucket of string slots, each written at most once.
+type Bucket = Box<[OnceLock<Arc<str>>]>;
+
+/// The strings of one shard, in buckets which double in size.
+///
+/// Bucket `b` holds the strings at indices `2^b - 1` up to `2^(b + 1) - 2`.
+/// A bucket is allocated once and never moved, and each slot is written once,
+/// so published strings can be read without taking the lock.
+struct Shard {
+    /// The number of strings in the shard. Held while adding a string.
+    len: Mutex<usize>,
+    buckets: [OnceLock<Bucket>; BUCKETS],
+}
+
+impl Shard {
+    /// Create a shard with room for `capacity` strings.
+    fn with_capacity(capacity: usize) -> Self {
+        let shard = Self {
+            len: Mutex::new(0),
+            buckets: std::array::from_fn(|_| OnceLock::new()),
+        };
+        if let Some(last) = capacity.checked_sub(1) {
+            for bucket in 0..=Self::slot(last).0 {
+                shard.bucket(bucket);
+            }
+        }
+        shard
+    }
+
+    /// Split an index into its bucket and its slot in that bucket.
+    #[inline]
+    fn slot(index: usize) -> (usize, usize) {
+        // an index is at most `Sym::MAX / shards`, so this cannot overflow.
+        let index = index + 1;
+        let bucket = index.ilog2() as usize;
+        (bucket, index - (1 << bucket))
+    }
+
+    /// Get a bucket, allocating it if needed.
+    fn bucket(&self, bucket: usize) -> &[OnceLock<Arc<str>>] {
+        self.buckets[bucket]
+            .get_or_init(|| (0..1usize << bucket).map(|_| OnceLock::new()).collect())
+    }
+
+    /// Get the string at `index`, if it has been published.
+    #[inline]
+    fn get(&self, index: usize) -> Option<&Arc<str>> {
+        if index == usize::MAX {
+            return None;
+        }
+        let (bucket, slot) = Self::slot(index);
+        self.buckets[bucket].get()?.get(slot)?.get()
+    }
+}
+
+/// Pick as many shards as `DashMap` does by default, a power of two well
+/// above the number of threads which can run at once.
+fn default_shard_amount() -> usize {
+    let threads = std::thread::available_parallelism().map_or(1, usize::from);
+    (threads * 4).next_power_of_two()
+}
+
+impl ShardedIntern {
+    /// Create a new sharded intern table.
+    /// The table uses the default `FxBuildHasher`.
+    pub fn new() -> Self {
+        Self::with_hasher(FxBuildHasher)
+    }
+
+    /// Create a new sharded intern table with the given capacity.
+    /// The table uses the default `FxBuildHasher`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, FxBuildHasher)
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher + Clone> ShardedIntern<Sym, S> {
+    /// Create a new sharded intern table which will use the given hasher.
+    pub fn with_hasher(hasher: S) -> Self {
+        Self::with_capacity_and_hasher(0, hasher)
+    }
+
+    /// Create a new sharded intern table with the given capacity, which will
+    /// use the given hasher.
+    pub fn with_capacity_and_hasher(capacity: usize, hasher: S) -> Self {
+        let amount = default_shard_amount();
+        let per_shard = capacity.div_ceil(amount);
+        Self {
+            map: DashMap::with_capacity_and_hasher_and_shard_amount(
+                capacity,
+                hasher.clone(),
+                amount,
+            ),
+            shards: (0..amount)
+                .map(|_| Shard::with_capacity(per_shard))
+                .collect(),
+            hasher,
+        }
+    }
+
+    /// Split an id into its shard and its index in that shard.
+    #[inline]
+    fn locate(&self, id: Sym) -> (usize, usize) {
+        let id = id.to_usize();
+        (id % self.shards.len(), id / self.shards.len())
+    }
+
+    /// Intern a string.
+    /// Returns the interned id.
+    /// If the string is already interned, returns the existing id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`ShardedIntern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::ShardedIntern;
+    ///
+    /// let intern = ShardedIntern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.intern(String::from("hello")), id);
+    /// ```
+    #[inline]
+    pub fn intern<V: AsRef<str>>(&self, input: V) -> Sym {
+        match self.try_intern(input) {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Intern a string.
+    /// Returns the interned id, or an error if the string could not be interned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::IdSpaceExhausted`] if the shard of the string
+    /// has run out of ids. Since ids are spread over the shards, this can
+    /// happen slightly before `Sym` runs out of ids overall.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::ShardedIntern;
+    ///
+    /// let intern = ShardedIntern::new();
+    /// let id = intern.try_intern("hello").unwrap();
+    /// assert_eq!(intern.lookup(id), "hello");
+    /// ```
+    pub fn try_intern<V: AsRef<str>>(&self, input: V) -> Result<Sym, InternError> {
+        let input = input.as_ref();
+        if let Some(id) = self.get(input) {
+            return Ok(id);
+        }
+
+        // the map entry stays locked until the string has been added to its
+        // shard, so no other thread can intern the same string meanwhile.
+        match self.map.entry(Arc::from(input)) {
+            Entry::Occupied(entry) => Ok(*entry.get()),
+            Entry::Vacant(entry) => {
+                let shard = self.hasher.hash_one(input) as usize % self.shards.len();
+                // a shard's length is only changed after its string is added,
+                // so it is never left inconsistent by a panic, and a poisoned
+                // lock is simply recovered.
+                let mut len = self.shards[shard]
+                    .len
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                let id = len
+                    .checked_mul(self.shards.len())
+                    .and_then(|id| id.checked_add(shard))
+                    .filter(|&id| id <= Sym::MAX)
+                    .ok_or(InternError::IdSpaceExhausted)?;
+                let id = Sym::from_usize(id);
+                let (bucket, slot) = Shard::slot(*len);
+                // only the holder of the lock writes the slot at `len`.
+                let _ = self.shards[shard].bucket(bucket)[slot].set(Arc::clone(entry.key()));
+                *len += 1;
+                entry.insert(id);
+                Ok(id)
+            }
+        }
+    }
+
+    /// Get the id of an already interned string.
+    /// Returns `None` if the string has not been interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::ShardedIntern;
+    ///
+    /// let intern = ShardedIntern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.get("hello"), Some(id));
+    /// assert_eq!(intern.get("world"), None);
+    /// ```
+    #[inline]
+    pub fn get(&self, input: &str) -> Option<Sym> {
+        self.map.get(input).map(|id| *id)
+    }
+
+    /// Check whether a string has already been interned.
+    #[inline]
+    pub fn contains(&self, input: &str) -> bool {
+        self.map.contains_key(input)
+    }
+
+    /// Lookup the interned string by id.
+    ///
+    /// The returned string stays valid for as long as the table is borrowed,
+    /// even while other threads keep interning. No lock is taken.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::ShardedIntern;
+    ///
+    /// let intern = ShardedIntern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.lookup(id), "hello");
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn lookup(&self, id: Sym) -> &str {
+        match self.try_lookup(id) {
+            Some(s) => s,
+            None => crate::invalid_id(id.to_usize(), self.len()),
+        }
+    }
+
+    /// Lookup the interned string by id.
+    /// Returns `None` if the id is not valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::ShardedIntern;
+    ///
+    /// let intern = ShardedIntern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.try_lookup(id), Some("hello"));
+    /// ```
+    #[inline]
+    pub fn try_lookup(&self, id: Sym) -> Option<&str> {
+        let (shard, index) = self.locate(id);
+        self.shards[shard].get(index).map(|s| &**s)
+    }
+
+    /// Returns the number of unique strings in the intern table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if no strings have been interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher + Clone + Default> Default for ShardedIntern<Sym, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher + Clone> fmt::Debug for ShardedIntern<Sym, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShardedIntern")
+            .field("len", &self.len())
+            .field("shards", &self.shards.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_intern_returns_same_ids() {
+        let intern = ShardedIntern::new();
+        let words: Vec<String> = (0..200).map(|i| i.to_string()).collect();
+
+        let ids = std::thread::scope(|s| {
+            let handles = (0..4)
+                .map(|t| {
+                    let words = &words;
+                    let intern = &intern;
+                    s.spawn(move || {
+                        // every thread interns the same strings in another order.
+                        let mut ids = vec![0; words.len()];
+                        for i in 0..words.len() {
+                            let i = (i + t * 50) % words.len();
+                            ids[i] = intern.intern(&words[i]);
+                        }
+                        ids
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        assert_eq!(intern.len(), words.len());
+        for thread_ids in &ids {
+            assert_eq!(thread_ids, &ids[0]);
+        }
+        for (w, &id) in words.iter().zip(&ids[0]) {
+            assert_eq!(intern.lookup(id), w);
+            assert_eq!(intern.get(w), Some(id));
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "too slow under miri")]
+    fn ids_are_unique_until_exhausted() {
+        let intern: ShardedIntern<u16> = ShardedIntern::default();
+        let hello = intern.lookup(intern.intern("hello"));
+        let mut ids = Vec::new();
+        for i in 0..70_000 {
+            match intern.try_intern(i.to_string()) {
+                Ok(id) => {
+                    assert_eq!(intern.lookup(id), i.to_string());
+                    ids.push(id);
+                }
+                Err(err) => assert_eq!(err, InternError::IdSpaceExhausted),
+            }
+        }
+        assert_eq!(hello, "hello");
+        assert_eq!(intern.len(), ids.len() + 1);
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(intern.len(), ids.len() + 1);
+        assert!(intern.len() <= u16::MAX as usize + 1);
+    }
+
+    #[test]
+    #[should_panic = "InternId 100 out of range (only 1 strings interned)"]
+    fn lookup_names_invalid_id() {
+        let intern = ShardedIntern::new();
+        intern.intern("a");
+        intern.lookup(100);
+    }
+
+    #[test]
+    fn shard_buckets_double_in_size() {
+        let slots: Vec<_> = (0..7).map(Shard::slot).collect();
+        assert_eq!(
+            slots,
+            [(0, 0), (1, 0), (1, 1), (2, 0), (2, 1), (2, 2), (2, 3)]
+        );
+        assert_eq!(
+            Shard::slot(usize::MAX - 1),
+            (BUCKETS - 1, (1 << (BUCKETS - 1)) - 1)
+        );
+
+        let shard = Shard::with_capacity(3);
+        assert!(shard.buckets[1].get().is_some());
+        assert!(shard.buckets[2].get().is_none());
+        assert_eq!(shard.get(0), None);
+        assert_eq!(shard.get(usize::MAX), None);
+    }
+}
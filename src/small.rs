@@ -0,0 +1,324 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::BuildHasher;
+use core::ops::Index;
+
+use hashbrown::hash_table::Entry;
+use hashbrown::HashTable;
+use rustc_hash::FxBuildHasher;
+
+use crate::{invalid_id, rehash, InternError, InternId, Symbol};
+
+/// The longest string which is stored inline. An inline string plus its
+/// length then takes exactly as much room as a boxed string and its tag.
+const INLINE_LEN: usize = 22;
+
+/// An interned string, stored inline if it is short enough.
+enum SmallStr {
+    Inline { len: u8, bytes: [u8; INLINE_LEN] },
+    Heap(Box<str>),
+}
+
+impl SmallStr {
+    #[inline]
+    fn new(input: &str) -> Self {
+        if input.len() <= INLINE_LEN {
+            let mut bytes = [0; INLINE_LEN];
+            bytes[..input.len()].copy_from_slice(input.as_bytes());
+            Self::Inline {
+                len: input.len() as u8,
+                bytes,
+            }
+        } else {
+            Self::Heap(Box::from(input))
+        }
+    }
+
+    #[inline]
+    fn as_str(&self) -> &str {
+        match self {
+            // SAFETY: the bytes were copied from a `&str` in `new`, and `len`
+            // is where that string ended, so they are valid UTF-8.
+            Self::Inline { len, bytes } => unsafe {
+                core::str::from_utf8_unchecked(&bytes[..*len as usize])
+            },
+            Self::Heap(s) => s,
+        }
+    }
+}
+
+impl core::ops::Deref for SmallStr {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+/// A string intern table which stores short strings inline.
+///
+/// Strings of up to 22 bytes are kept directly in the list of strings,
+/// instead of in a heap allocation of their own like [`Intern`](crate::Intern)
+/// does. Interning short strings, like operators or single letters, then does
+/// not allocate at all once the list has room. Longer strings are boxed as
+/// usual. Each string takes 24 bytes in the list either way.
+///
+/// # Examples
+///
+/// ```
+/// use intern_string::SmallIntern;
+///
+/// let mut intern = SmallIntern::new();
+/// let id = intern.intern("+=");
+/// assert_eq!(intern.intern("+="), id);
+/// assert_eq!(intern.lookup(id), "+=");
+/// ```
+pub struct SmallIntern<Sym = InternId, S = FxBuildHasher> {
+    data: HashTable<Sym>,
+    list: Vec<SmallStr>,
+    hasher: S,
+}
+
+impl SmallIntern {
+    /// Create a new small string intern table.
+    /// The table uses the default `FxBuildHasher`.
+    pub fn new() -> Self {
+        Self::with_hasher(FxBuildHasher)
+    }
+
+    /// Create a new small string intern table with the given capacity.
+    /// The table uses the default `FxBuildHasher`.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: HashTable::with_capacity(capacity),
+            list: Vec::with_capacity(capacity),
+            hasher: FxBuildHasher,
+        }
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> SmallIntern<Sym, S> {
+    /// Create a new small string intern table which will use the given hasher.
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            data: HashTable::new(),
+            list: Vec::new(),
+            hasher,
+        }
+    }
+
+    /// Intern a string.
+    /// Returns the interned id.
+    /// If the string is already interned, returns the existing id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every id is already in use. See [`SmallIntern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::SmallIntern;
+    ///
+    /// let mut intern = SmallIntern::new();
+    /// let id = intern.intern("x");
+    /// assert_eq!(intern.intern(String::from("x")), id);
+    /// ```
+    #[inline]
+    pub fn intern<V: AsRef<str>>(&mut self, input: V) -> Sym {
+        match self.try_intern(input) {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Intern a string.
+    /// Returns the interned id, or an error if the string could not be interned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`InternError::IdSpaceExhausted`] if every id is already in use.
+    #[inline]
+    pub fn try_intern<V: AsRef<str>>(&mut self, input: V) -> Result<Sym, InternError> {
+        let input = input.as_ref();
+        let hash = self.hasher.hash_one(input);
+        let Self { data, list, hasher } = self;
+        let entry = data.entry(
+            hash,
+            |id| list[id.to_usize()].as_str() == input,
+            rehash(list, hasher),
+        );
+        match entry {
+            Entry::Occupied(entry) => Ok(*entry.get()),
+            Entry::Vacant(entry) => {
+                if list.len() > Sym::MAX {
+                    return Err(InternError::IdSpaceExhausted);
+                }
+                let id = Sym::from_usize(list.len());
+                list.push(SmallStr::new(input));
+                entry.insert(id);
+                Ok(id)
+            }
+        }
+    }
+
+    /// Get the id of an already interned string.
+    /// Returns `None` if the string has not been interned.
+    #[inline]
+    pub fn get(&self, input: &str) -> Option<Sym> {
+        self.data
+            .find(self.hasher.hash_one(input), |id| {
+                self.list[id.to_usize()].as_str() == input
+            })
+            .copied()
+    }
+
+    /// Check whether a string has already been interned.
+    #[inline]
+    pub fn contains(&self, input: &str) -> bool {
+        self.get(input).is_some()
+    }
+
+    /// Lookup the interned string by id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    #[track_caller]
+    pub fn lookup(&self, id: Sym) -> &str {
+        match self.list.get(id.to_usize()) {
+            Some(s) => s.as_str(),
+            None => invalid_id(id.to_usize(), self.list.len()),
+        }
+    }
+
+    /// Lookup the interned string by id.
+    /// Returns `None` if the id is not valid.
+    #[inline]
+    pub fn try_lookup(&self, id: Sym) -> Option<&str> {
+        self.list.get(id.to_usize()).map(SmallStr::as_str)
+    }
+
+    /// Returns the number of unique strings in the intern table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Returns `true` if no strings have been interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Iterate over all interned strings in insertion order.
+    /// The position of each string in the iteration is its id.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.list.iter().map(SmallStr::as_str)
+    }
+
+    /// Remove every string from the intern table.
+    /// All previously returned ids become invalid and new ids start at 0 again.
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.list.clear();
+    }
+}
+
+impl<Sym, S: Default> Default for SmallIntern<Sym, S> {
+    fn default() -> Self {
+        Self {
+            data: HashTable::new(),
+            list: Vec::new(),
+            hasher: S::default(),
+        }
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> fmt::Debug for SmallIntern<Sym, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SmallIntern")
+            .field("len", &self.len())
+            .field("strings", &self.iter().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> Index<Sym> for SmallIntern<Sym, S> {
+    type Output = str;
+
+    /// Lookup the interned string by id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    #[inline]
+    #[track_caller]
+    fn index(&self, id: Sym) -> &str {
+        self.lookup(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stores_short_strings_inline() {
+        let mut interner = SmallIntern::new();
+        let empty = interner.intern("");
+        let short = interner.intern("ab");
+        let edge = interner.intern("a".repeat(INLINE_LEN));
+        let long = interner.intern("b".repeat(INLINE_LEN + 1));
+        let unicode = interner.intern("héllo wörld");
+
+        assert!(matches!(
+            interner.list[empty.to_usize()],
+            SmallStr::Inline { len: 0, .. }
+        ));
+        assert!(matches!(
+            interner.list[edge.to_usize()],
+            SmallStr::Inline { .. }
+        ));
+        assert!(matches!(interner.list[long.to_usize()], SmallStr::Heap(_)));
+        assert_eq!(interner.lookup(empty), "");
+        assert_eq!(&interner[short], "ab");
+        assert_eq!(interner.lookup(edge), "a".repeat(INLINE_LEN));
+        assert_eq!(interner.lookup(long), "b".repeat(INLINE_LEN + 1));
+        assert_eq!(interner.get("héllo wörld"), Some(unicode));
+        assert_eq!(interner.intern("ab"), short);
+        assert_eq!(interner.try_lookup(5), None);
+        assert_eq!(interner.len(), 5);
+
+        assert_eq!(
+            core::mem::size_of::<SmallStr>(),
+            core::mem::size_of::<Box<str>>() + 8
+        );
+    }
+
+    #[test]
+    fn survives_growth() {
+        let mut interner = SmallIntern::with_capacity(1);
+        let ids: Vec<u32> = (0..1000).map(|i| interner.intern(i.to_string())).collect();
+        for (i, id) in ids.into_iter().enumerate() {
+            assert_eq!(interner.lookup(id), i.to_string());
+            assert_eq!(interner.get(&i.to_string()), Some(id));
+        }
+
+        interner.clear();
+        assert!(interner.is_empty());
+        assert_eq!(interner.intern("1"), 0);
+    }
+
+    #[test]
+    #[should_panic = "InternId 1 out of range (only 1 strings interned)"]
+    fn lookup_names_invalid_id() {
+        let mut interner = SmallIntern::new();
+        interner.intern("a");
+        interner.lookup(1);
+    }
+}
@@ -0,0 +1,150 @@
+use core::num::NonZeroU32;
+
+use crate::{InternId, Symbol};
+
+/// A strongly typed id of an interned string.
+///
+/// `SymbolId` wraps an [`InternId`] so that ids of interned strings cannot be
+/// mixed up with other integers, like indices into unrelated tables. Use it as
+/// the id type of an intern table to get `SymbolId`s instead of raw ids.
+///
+/// # Examples
+///
+/// ```
+/// use intern_string::{Intern, InternId, SymbolId};
+///
+/// let mut intern = Intern::<SymbolId>::default();
+/// let id = intern.intern("hello");
+/// assert_eq!(intern.lookup(id), "hello");
+/// assert_eq!(InternId::from(id), 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct SymbolId(InternId);
+
+impl Symbol for SymbolId {
+    const MAX: usize = <InternId as Symbol>::MAX;
+
+    #[inline]
+    fn from_usize(index: usize) -> Self {
+        Self(InternId::from_usize(index))
+    }
+
+    #[inline]
+    fn to_usize(self) -> usize {
+        self.0.to_usize()
+    }
+}
+
+impl From<InternId> for SymbolId {
+    #[inline]
+    fn from(id: InternId) -> Self {
+        Self(id)
+    }
+}
+
+impl From<SymbolId> for InternId {
+    #[inline]
+    fn from(id: SymbolId) -> Self {
+        id.0
+    }
+}
+
+/// A strongly typed id of an interned string which is never zero.
+///
+/// Ids start at 1, and the string with id `n` is stored at position `n - 1`.
+/// Since zero is reserved, `Option<NonZeroSymbolId>` is as small as
+/// `NonZeroSymbolId` itself, which helps when storing many optional ids.
+/// The price is a single id less than [`InternId`] can hold.
+///
+/// # Examples
+///
+/// ```
+/// use intern_string::{Intern, NonZeroSymbolId};
+///
+/// let mut intern = Intern::<NonZeroSymbolId>::default();
+/// let id = intern.intern("hello");
+/// assert_eq!(u32::from(id), 1);
+/// assert_eq!(intern.lookup(id), "hello");
+/// assert_eq!(
+///     std::mem::size_of::<Option<NonZeroSymbolId>>(),
+///     std::mem::size_of::<u32>(),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct NonZeroSymbolId(NonZeroU32);
+
+impl Symbol for NonZeroSymbolId {
+    const MAX: usize = <InternId as Symbol>::MAX - 1;
+
+    #[inline]
+    fn from_usize(index: usize) -> Self {
+        Self(NonZeroU32::MIN.saturating_add(index as u32))
+    }
+
+    #[inline]
+    fn to_usize(self) -> usize {
+        (self.0.get() - 1) as usize
+    }
+}
+
+impl From<NonZeroU32> for NonZeroSymbolId {
+    #[inline]
+    fn from(id: NonZeroU32) -> Self {
+        Self(id)
+    }
+}
+
+impl From<NonZeroSymbolId> for NonZeroU32 {
+    #[inline]
+    fn from(id: NonZeroSymbolId) -> Self {
+        id.0
+    }
+}
+
+impl From<NonZeroSymbolId> for InternId {
+    #[inline]
+    fn from(id: NonZeroSymbolId) -> Self {
+        id.0.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Intern;
+
+    #[test]
+    fn symbol_id_round_trips() {
+        let mut interner = Intern::<SymbolId>::default();
+        let hello = interner.intern("hello");
+        let world = interner.intern("world");
+        assert!(hello < world);
+        assert_eq!(interner.get("world"), Some(SymbolId::from(1)));
+        assert_eq!(interner.lookup(SymbolId::from(0)), "hello");
+        assert_eq!(InternId::from(world), 1);
+        assert_eq!(
+            core::mem::size_of::<SymbolId>(),
+            core::mem::size_of::<InternId>()
+        );
+    }
+
+    #[test]
+    fn non_zero_ids_start_at_one() {
+        let mut interner = Intern::<NonZeroSymbolId>::default();
+        let hello = interner.intern("hello");
+        let world = interner.intern("world");
+        assert_eq!(InternId::from(hello), 1);
+        assert_eq!(InternId::from(world), 2);
+        assert_eq!(interner.lookup(world), "world");
+        assert_eq!(interner.iter_with_ids().next(), Some((hello, "hello")));
+
+        let unused = NonZeroSymbolId::from(NonZeroU32::new(3).unwrap());
+        assert_eq!(interner.try_lookup(unused), None);
+        assert_eq!(
+            core::mem::size_of::<Option<NonZeroSymbolId>>(),
+            core::mem::size_of::<NonZeroSymbolId>()
+        );
+    }
+}
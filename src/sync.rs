@@ -0,0 +1,325 @@
+use std::fmt;
+use std::hash::BuildHasher;
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use rustc_hash::FxBuildHasher;
+
+use crate::{Intern, InternError, InternId, Symbol};
+
+/// A thread-safe string intern table.
+///
+/// `SyncIntern` wraps an [`Intern`] in a `RwLock`. Lookups and hits take a
+/// shared lock, so they can run in parallel; only interning a new string
+/// takes the exclusive lock. Identical strings always resolve to the same id,
+/// regardless of which thread interned them first.
+///
+/// Strings returned by [`SyncIntern::lookup`] borrow the table itself rather
+/// than the lock guard. This is sound because every interned string lives in
+/// its own heap allocation which is never moved or freed while the table is
+/// shared; removing strings requires `&mut self` or consuming the table, and
+/// the borrow checker will not allow that while any returned `&str` is alive.
+///
+/// # Examples
+///
+/// ```
+/// use intern_string::SyncIntern;
+///
+/// let intern = SyncIntern::new();
+/// std::thread::scope(|s| {
+///     s.spawn(|| intern.intern("hello"));
+///     s.spawn(|| intern.intern("hello"));
+/// });
+/// assert_eq!(intern.len(), 1);
+/// ```
+pub struct SyncIntern<Sym = InternId, S = FxBuildHasher> {
+    inner: RwLock<Intern<'static, Sym, S>>,
+}
+
+impl SyncIntern {
+    /// Create a new thread-safe intern table.
+    pub fn new() -> Self {
+        Self::from(Intern::new())
+    }
+
+    /// Create a new thread-safe intern table with the given capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::from(Intern::with_capacity(capacity))
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> SyncIntern<Sym, S> {
+    /// Acquire the shared lock.
+    ///
+    /// The intern table is never left in an inconsistent state by a panic, so
+    /// a poisoned lock is simply recovered.
+    fn read(&self) -> RwLockReadGuard<'_, Intern<'static, Sym, S>> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Acquire the exclusive lock, recovering from poisoning like [`Self::read`].
+    fn write(&self) -> RwLockWriteGuard<'_, Intern<'static, Sym, S>> {
+        self.inner.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Intern a string.
+    /// Returns the interned id.
+    /// If the string is already interned, returns the existing id.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`SyncIntern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::SyncIntern;
+    ///
+    /// let intern = SyncIntern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.lookup(id), "hello");
+    /// ```
+    #[inline]
+    pub fn intern<V: Into<String> + AsRef<str>>(&self, input: V) -> Sym {
+        match self.try_intern(input) {
+            Ok(id) => id,
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    /// Intern a string.
+    /// Returns the interned id, or an error if the string could not be interned.
+    ///
+    /// # Errors
+    ///
+    /// Fails for the same reasons as [`Intern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::SyncIntern;
+    ///
+    /// let intern = SyncIntern::new();
+    /// let id = intern.try_intern("hello").unwrap();
+    /// assert_eq!(intern.lookup(id), "hello");
+    /// ```
+    pub fn try_intern<V: Into<String> + AsRef<str>>(&self, input: V) -> Result<Sym, InternError> {
+        if let Some(id) = self.get(input.as_ref()) {
+            return Ok(id);
+        }
+
+        // another thread may have interned the string between releasing the
+        // shared lock and acquiring the exclusive one, which `try_intern`
+        // handles by returning the existing id.
+        self.write().try_intern(input)
+    }
+
+    /// Intern a string, returning both its id and the interned copy.
+    ///
+    /// The returned string borrows the table, not a lock guard, so it stays
+    /// valid while more strings are interned, from this thread or any other.
+    /// It is only invalidated by operations which need `&mut self` or consume
+    /// the table, like clearing it through [`SyncIntern::get_mut`], which the
+    /// borrow checker rules out while the string is in use.
+    /// [`Intern::get_or_intern`] is the single threaded equivalent, whose
+    /// string borrows the table mutably instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string could not be interned. See [`SyncIntern::try_intern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::SyncIntern;
+    ///
+    /// let intern = SyncIntern::new();
+    /// let (id, hello) = intern.intern_ref("hello");
+    /// intern.intern("world");
+    /// assert_eq!(hello, "hello");
+    /// assert_eq!(intern.lookup(id), hello);
+    /// ```
+    #[inline]
+    pub fn intern_ref<V: Into<String> + AsRef<str>>(&self, input: V) -> (Sym, &str) {
+        let id = self.intern(input);
+        (id, self.lookup(id))
+    }
+
+    /// Get the id of an already interned string.
+    /// Returns `None` if the string has not been interned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::SyncIntern;
+    ///
+    /// let intern = SyncIntern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.get("hello"), Some(id));
+    /// assert_eq!(intern.get("world"), None);
+    /// ```
+    #[inline]
+    pub fn get(&self, input: &str) -> Option<Sym> {
+        self.read().get(input)
+    }
+
+    /// Lookup the interned string by id.
+    ///
+    /// The returned string stays valid for as long as the table is borrowed,
+    /// even while other threads keep interning.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the id is not valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::SyncIntern;
+    ///
+    /// let intern = SyncIntern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.lookup(id), "hello");
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn lookup(&self, id: Sym) -> &str {
+        let s: *const str = self.read().lookup(id);
+        // SAFETY: the string is either `'static` or owned by its own heap
+        // allocation in the inner table. That allocation does not move when
+        // the table grows and is only freed through `&mut self` or by
+        // consuming `self`, neither of which can happen while the returned
+        // borrow of `self` is alive.
+        unsafe { &*s }
+    }
+
+    /// Lookup the interned string by id.
+    /// Returns `None` if the id is not valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use intern_string::SyncIntern;
+    ///
+    /// let intern = SyncIntern::new();
+    /// let id = intern.intern("hello");
+    /// assert_eq!(intern.try_lookup(id), Some("hello"));
+    /// ```
+    #[inline]
+    pub fn try_lookup(&self, id: Sym) -> Option<&str> {
+        let s: *const str = self.read().try_lookup(id)?;
+        // SAFETY: see `lookup`.
+        Some(unsafe { &*s })
+    }
+
+    /// Returns the number of unique strings in the intern table.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    /// Returns `true` if no strings have been interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.read().is_empty()
+    }
+
+    /// Get mutable access to the inner intern table.
+    /// No locking is needed since this borrows the table exclusively.
+    pub fn get_mut(&mut self) -> &mut Intern<'static, Sym, S> {
+        self.inner.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Consume the thread-safe table, returning the inner intern table.
+    pub fn into_inner(self) -> Intern<'static, Sym, S> {
+        self.inner
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<Sym, S> From<Intern<'static, Sym, S>> for SyncIntern<Sym, S> {
+    fn from(intern: Intern<'static, Sym, S>) -> Self {
+        Self {
+            inner: RwLock::new(intern),
+        }
+    }
+}
+
+impl<Sym, S: Default> Default for SyncIntern<Sym, S> {
+    fn default() -> Self {
+        Self {
+            inner: RwLock::new(Intern::default()),
+        }
+    }
+}
+
+impl<Sym: Symbol, S: BuildHasher> fmt::Debug for SyncIntern<Sym, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SyncIntern").field(&*self.read()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_intern_returns_same_ids() {
+        let intern = SyncIntern::new();
+        let words = ["hello", "world", "foo", "bar", "baz"];
+
+        let ids = std::thread::scope(|s| {
+            let handles = (0..4)
+                .map(|_| {
+                    s.spawn(|| {
+                        words
+                            .iter()
+                            .cycle()
+                            .take(100)
+                            .map(|w| intern.intern(*w))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        assert_eq!(intern.len(), words.len());
+        for thread_ids in &ids {
+            assert_eq!(thread_ids, &ids[0]);
+        }
+        for (w, id) in words.iter().zip(&ids[0]) {
+            assert_eq!(intern.lookup(*id), *w);
+        }
+    }
+
+    #[test]
+    fn lookup_survives_growth() {
+        let intern = SyncIntern::with_capacity(1);
+        let hello = intern.lookup(intern.intern("hello"));
+        for i in 0..100 {
+            intern.intern(i.to_string());
+        }
+        assert_eq!(hello, "hello");
+        assert_eq!(intern.try_lookup(101), None);
+        assert_eq!(intern.into_inner().len(), 101);
+    }
+
+    #[test]
+    fn intern_ref_outlives_later_interning() {
+        let intern = SyncIntern::new();
+        let (id, hello) = intern.intern_ref(String::from("hello"));
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for i in 0..100 {
+                    intern.intern(i.to_string());
+                }
+            });
+        });
+        assert_eq!(intern.intern_ref("hello"), (id, hello));
+        assert_eq!(hello, "hello");
+    }
+}